@@ -13,35 +13,199 @@
 // limitations under the License.
 
 use itertools::Itertools;
-use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{Display, Formatter};
 use std::time::Duration;
 
-use crate::await_tree::tree::TreeView;
+use crate::await_tree::current::{CurrentAwaitReport, EXCHANGE_SPAN_KINDS};
+use crate::await_tree::finding::{rank_findings, sort_findings, Finding, FindingKind};
+use crate::await_tree::graph::{ActorGraph, InputEdge};
+use crate::await_tree::knowledge::KnowledgeBase;
+use crate::await_tree::render::RenderOptions;
+use crate::await_tree::shape::ShapeGroup;
+use crate::await_tree::stats::{Distribution, SpanKindStats};
+use crate::await_tree::tree::{SpanNodeView, TreeView, SLOW_SPAN_NS};
 use crate::await_tree::utils::extract_actor_traces;
-use crate::await_tree::utils::parse_tree_from_trace;
+use crate::await_tree::utils::parse_traces;
+use crate::await_tree::utils::{normalize_span_name, parse_executor, upstream_actor_id};
 
 type IoInfo = String;
+
+/// Number of findings listed in the ranked section of the summary.
+pub(crate) const RANKED_FINDINGS_LIMIT: usize = 10;
+/// Minimum number of actors in a fragment to tell stragglers from their siblings.
+const MIN_STRAGGLER_SIBLINGS: usize = 4;
+/// Number of span kinds listed in the slowest span kinds section of the summary.
+pub(crate) const TOP_SPAN_KINDS_LIMIT: usize = 10;
+
+/// A join executor whose output keeps its downstream actors busy while their epochs cannot
+/// complete. See the special IB Tree in [`TreeView::has_fast_children`].
+#[derive(Debug, Clone)]
+pub struct JoinAmplification {
+    /// Name of the job the join belongs to.
+    pub job_name: String,
+    /// The join executor span of the first upstream actor, e.g. `HashJoin 1500000003`.
+    pub join_executor: String,
+    /// Operator id of the join executor, shared by all actors of the fragment.
+    pub operator_id: u32,
+    /// All actors running the same join operator, i.e. the join fragment.
+    pub fragment_actors: BTreeSet<u32>,
+    /// Join actors feeding the stuck downstream actors.
+    pub upstream_actors: BTreeSet<u32>,
+    /// Downstream actors with an old epoch but repeatedly fast children.
+    pub downstream_actors: BTreeSet<u32>,
+}
+
+/// How long a span must be pending to be considered slow by the rules and the reports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlowThreshold {
+    Fixed(Duration),
+    /// Derived from the dump itself: `ratio` times the median age of the actors' `Epoch`
+    /// spans, or of all spans if the dump contains no `Epoch` span, but at least `min` and at
+    /// most `max`.
+    ///
+    /// This adapts the rules to clusters with sub-second barriers. The cap keeps a stalled
+    /// cluster, where every epoch is old, from raising the threshold above its own stall.
+    Adaptive {
+        ratio: f64,
+        min: Duration,
+        max: Duration,
+    },
+}
+
+impl SlowThreshold {
+    /// The adaptive threshold with the default parameters.
+    pub fn adaptive() -> Self {
+        SlowThreshold::Adaptive {
+            ratio: 2.0,
+            min: Duration::from_millis(100),
+            max: Duration::from_nanos(SLOW_SPAN_NS as u64),
+        }
+    }
+
+    /// Resolves the threshold against the trees of a dump.
+    fn resolve(&self, trees: &BTreeMap<u32, TreeView>) -> ResolvedThreshold {
+        match *self {
+            SlowThreshold::Fixed(threshold) => ResolvedThreshold {
+                threshold_ns: threshold.as_nanos(),
+                basis: None,
+            },
+            SlowThreshold::Adaptive { ratio, min, max } => {
+                let epoch_ages = trees.values().filter_map(|tree| tree.epoch_age_ns());
+                let (basis, median_ns) = match median(epoch_ages) {
+                    Some(median_ns) => ("epoch age", median_ns),
+                    None => {
+                        let span_elapsed = trees.values().flat_map(|tree| {
+                            tree.tree
+                                .iter()
+                                .filter(|node| !node.span.is_long_running)
+                                .map(|node| node.elapsed_ns)
+                        });
+                        let median_ns = median(span_elapsed).unwrap_or(SLOW_SPAN_NS);
+                        ("span elapsed time", median_ns)
+                    }
+                };
+                let threshold_ns = (median_ns as f64 * ratio) as u128;
+                let mut basis = format!(
+                    "{}x the median {} {:.3}s",
+                    ratio,
+                    basis,
+                    median_ns as f64 / 1_000_000_000.0
+                );
+                if threshold_ns > max.as_nanos() {
+                    basis += &format!(", capped at {:.3}s", max.as_secs_f64());
+                }
+                ResolvedThreshold {
+                    threshold_ns: threshold_ns.clamp(min.as_nanos(), max.as_nanos()),
+                    basis: Some(basis),
+                }
+            }
+        }
+    }
+}
+
+/// Returns the median of the values, keeping duplicates so that common values weigh more
+/// than rare ones.
+fn median(values: impl Iterator<Item = u128>) -> Option<u128> {
+    let mut values = values.collect_vec();
+    values.sort_unstable();
+    values.get(values.len().checked_sub(1)? / 2).copied()
+}
+
+impl Default for SlowThreshold {
+    fn default() -> Self {
+        SlowThreshold::Fixed(Duration::from_nanos(SLOW_SPAN_NS as u64))
+    }
+}
+
+/// The threshold of slow spans chosen for an analysis.
+#[derive(Debug, Clone)]
+pub(crate) struct ResolvedThreshold {
+    pub(crate) threshold_ns: u128,
+    /// How an adaptive threshold was derived, `None` for a fixed threshold.
+    pub(crate) basis: Option<String>,
+}
+
+impl Display for ResolvedThreshold {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.3}s", self.threshold_ns as f64 / 1_000_000_000.0)?;
+        match &self.basis {
+            Some(basis) => write!(f, " (adaptive: {})", basis),
+            None => write!(f, " (fixed)"),
+        }
+    }
+}
+
+/// Options of [`AnalyzeSummary::from_trees_with_config`].
+#[derive(Debug, Clone, Default)]
+pub struct AnalyzeConfig {
+    /// Known stall patterns attached to the matching findings as hints.
+    pub knowledge_base: KnowledgeBase,
+    pub slow_threshold: SlowThreshold,
+    /// How the bottleneck trees are rendered in the report.
+    pub render_options: RenderOptions,
+}
+
 #[derive(Debug, Clone)]
 pub struct AnalyzeSummary {
-    has_fast_children_actors: HashMap<u32, TreeView>,
+    pub(crate) has_fast_children_actors: BTreeMap<u32, TreeView>,
     /// IO bound rule usually match a lot of Trees once the storage is unavailable, as a
     /// result, too many trees are outputed. We only output the actor ids here.
-    io_bound_actors: HashMap<IoInfo, HashSet<u32>>,
+    pub(crate) io_bound_actors: BTreeMap<IoInfo, BTreeSet<u32>>,
+    pub(crate) join_amplifications: Vec<JoinAmplification>,
+    /// Circular waits between actors, see [`ActorGraph::find_cycles`].
+    pub(crate) wait_cycles: Vec<Vec<InputEdge>>,
+    /// All findings, ordered from the most to the least likely root cause.
+    pub(crate) findings: Vec<Finding>,
+    pub(crate) current_awaits: CurrentAwaitReport,
 
     // some intermediate results for debug
-    total_actors_analyzed: usize,
-    actor_elapsed_ns: BTreeSet<(u128, u32)>,
-    actor_name: HashMap<u32, String>,
+    pub(crate) total_actors_analyzed: usize,
+    pub(crate) slow_threshold: ResolvedThreshold,
+    pub(crate) render_options: RenderOptions,
+    pub(crate) actor_elapsed_ns: BTreeSet<(u128, u32)>,
+    /// The age of the oldest `Epoch` span of each actor.
+    pub(crate) epoch_elapsed_ns: BTreeSet<(u128, u32)>,
+    /// Statistics of the non-long-running spans of all actors, keyed by span kind.
+    pub(crate) span_stats: BTreeMap<String, SpanKindStats>,
+    pub(crate) actor_name: BTreeMap<u32, String>,
 }
 
 impl AnalyzeSummary {
     pub fn new() -> Self {
         Self {
             total_actors_analyzed: 0,
-            has_fast_children_actors: HashMap::new(),
-            io_bound_actors: HashMap::new(),
+            slow_threshold: SlowThreshold::default().resolve(&BTreeMap::new()),
+            render_options: RenderOptions::default(),
+            has_fast_children_actors: BTreeMap::new(),
+            io_bound_actors: BTreeMap::new(),
+            join_amplifications: Vec::new(),
+            wait_cycles: Vec::new(),
+            findings: Vec::new(),
+            current_awaits: Default::default(),
             actor_elapsed_ns: Default::default(),
+            epoch_elapsed_ns: Default::default(),
+            span_stats: Default::default(),
             actor_name: Default::default(),
         }
     }
@@ -51,43 +215,228 @@ impl AnalyzeSummary {
     where
         M: IntoIterator<Item = (&'a u32, &'a String)>,
     {
+        Self::from_traces_with_config(actor_traces, &AnalyzeConfig::default())
+    }
+
+    pub fn from_traces_with_config<'a, M>(
+        actor_traces: M,
+        config: &AnalyzeConfig,
+    ) -> anyhow::Result<Self>
+    where
+        M: IntoIterator<Item = (&'a u32, &'a String)>,
+    {
+        let trees = parse_traces(actor_traces)?;
+        Ok(Self::from_trees_with_config(&trees, config))
+    }
+
+    /// Analyzes the trees parsed from each actor's trace, keyed by actor id.
+    pub fn from_trees(trees: &BTreeMap<u32, TreeView>) -> Self {
+        Self::from_trees_with_config(trees, &AnalyzeConfig::default())
+    }
+
+    pub fn from_trees_with_config(trees: &BTreeMap<u32, TreeView>, config: &AnalyzeConfig) -> Self {
         let mut summary = Self::new();
-        for (actor_id, trace) in actor_traces {
+        summary.slow_threshold = config.slow_threshold.resolve(trees);
+        let slow_ns = summary.slow_threshold.threshold_ns;
+        summary.render_options = config.render_options;
+        for (actor_id, tree) in trees {
             summary.total_actors_analyzed += 1;
-            let tree = parse_tree_from_trace(trace)?;
             // >> Actor 2029188
             // Actor 2029188: `developer_balances_mv` [11105.089s]
             //   Epoch 8782342183256064 [!!! 10771.678s]
             //     StreamScan 1EF68400002736 [!!! 10771.682s]
             //       Merge 1EF68400000000 [!!! 10771.682s]
-            let actor_name = tree
-                .tree
-                .span
-                .name
-                .clone()
-                .split("`")
-                .nth(1)
-                .unwrap()
-                .to_string();
-            summary.actor_name.insert(*actor_id, actor_name);
+            if let Some(job_name) = tree.job_name() {
+                summary.actor_name.insert(*actor_id, job_name.to_owned());
+            }
             summary
                 .actor_elapsed_ns
                 .insert((tree.tree.elapsed_ns, *actor_id));
-            if tree.has_fast_children() {
+            if let Some(epoch_elapsed_ns) = tree.epoch_age_ns() {
+                summary
+                    .epoch_elapsed_ns
+                    .insert((epoch_elapsed_ns, *actor_id));
+            }
+            for node in std::iter::once(&tree.tree)
+                .chain(&tree.detached)
+                .flat_map(|root| root.iter())
+                .filter(|node| !node.span.is_long_running)
+            {
+                summary
+                    .span_stats
+                    .entry(normalize_span_name(&node.span.name))
+                    .or_default()
+                    .add(*actor_id, node.elapsed_ns);
+            }
+            if let Some((path, _)) = tree.fast_children_span(slow_ns) {
                 summary
                     .has_fast_children_actors
                     .insert(*actor_id, tree.clone());
+                summary.findings.push(Finding::new(
+                    *actor_id,
+                    FindingKind::FastChildren,
+                    tree,
+                    path,
+                    slow_ns,
+                ));
+            }
+            tree.find_io_bound(*actor_id, &mut summary.io_bound_actors, slow_ns);
+            if let Some((path, _)) = tree
+                .io_bound_spans(slow_ns)
+                .max_by_key(|(_, node)| node.elapsed_ns)
+            {
+                summary.findings.push(Finding::new(
+                    *actor_id,
+                    FindingKind::IoBound,
+                    tree,
+                    path,
+                    slow_ns,
+                ));
+            }
+        }
+        summary.join_amplifications = find_join_amplifications(trees, slow_ns);
+        for join in &summary.join_amplifications {
+            for actor_id in &join.upstream_actors {
+                let tree = &trees[actor_id];
+                if let Some((path, _, _)) = tree.find_join_executor() {
+                    summary.findings.push(Finding::new(
+                        *actor_id,
+                        FindingKind::JoinAmplification,
+                        tree,
+                        path,
+                        slow_ns,
+                    ));
+                }
             }
-            tree.find_io_bound(*actor_id, &mut summary.io_bound_actors);
         }
-        Ok(summary)
+        summary.findings.extend(find_stragglers(trees, slow_ns));
+        let graph = ActorGraph::with_slow_threshold(trees, slow_ns);
+        summary.wait_cycles = graph
+            .find_cycles()
+            .into_iter()
+            .map(|cycle| cycle.into_iter().cloned().collect())
+            .collect();
+        rank_findings(&mut summary.findings, trees, &graph, slow_ns);
+        for finding in &mut summary.findings {
+            finding.hints = config.knowledge_base.lookup(finding).cloned().collect();
+        }
+        summary.current_awaits = CurrentAwaitReport::new(trees);
+        summary
+    }
+
+    /// Returns the distribution of actor uptimes.
+    pub fn actor_elapsed(&self) -> Distribution {
+        Distribution::new(&self.actor_elapsed_ns)
+    }
+
+    /// Returns the distribution of the age of the oldest `Epoch` span of each actor.
+    pub fn epoch_elapsed(&self) -> Distribution {
+        Distribution::new(&self.epoch_elapsed_ns)
+    }
+
+    /// Returns the `n` span kinds with the largest maximum elapsed time across all actors.
+    pub fn top_span_kinds(&self, n: usize) -> Vec<(&str, &SpanKindStats)> {
+        self.span_stats
+            .iter()
+            .sorted_by(|(_, a), (_, b)| (b.max_ns, b.total_ns).cmp(&(a.max_ns, a.total_ns)))
+            .take(n)
+            .map(|(kind, stats)| (kind.as_str(), stats))
+            .collect()
+    }
+
+    /// Returns all findings, ordered from the most to the least likely root cause.
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    /// Returns the threshold in nanoseconds above which spans were considered slow.
+    pub fn slow_threshold_ns(&self) -> u128 {
+        self.slow_threshold.threshold_ns
+    }
+
+    /// Returns the circular waits between actors, see [`ActorGraph::find_cycles`].
+    pub fn wait_cycles(&self) -> &[Vec<InputEdge>] {
+        &self.wait_cycles
+    }
+
+    /// Returns what each actor is awaiting at the moment of the dump.
+    pub fn current_awaits(&self) -> &CurrentAwaitReport {
+        &self.current_awaits
+    }
+
+    /// Groups the fast children actors by the shape of their trees, following the ranking so
+    /// that the most likely root cause comes first. Each group comes with the bottleneck paths
+    /// of its actors.
+    pub(crate) fn fast_children_groups(&self) -> Vec<(ShapeGroup<'_>, Vec<&[usize]>)> {
+        let ranked_findings = self
+            .findings
+            .iter()
+            .filter(|finding| finding.kind == FindingKind::FastChildren)
+            .collect_vec();
+        ShapeGroup::group(ranked_findings.iter().filter_map(|finding| {
+            let tree = self.has_fast_children_actors.get(&finding.actor_id)?;
+            Some((finding.actor_id, tree))
+        }))
+        .into_iter()
+        .map(|group| {
+            let bottlenecks = ranked_findings
+                .iter()
+                .filter(|finding| group.actor_ids.contains(&finding.actor_id))
+                .map(|finding| finding.evidence.path_indices.as_slice())
+                .collect();
+            (group, bottlenecks)
+        })
+        .collect()
+    }
+
+    /// Returns the IO bound actors grouped by the IO span, and then by the actor name.
+    pub(crate) fn io_bound_groups(&self) -> Vec<(&str, BTreeMap<&str, BTreeSet<u32>>)> {
+        self.io_bound_actors
+            .iter()
+            .map(|(io_info, actor_ids)| {
+                let mut actor_names: BTreeMap<&str, BTreeSet<u32>> = BTreeMap::new();
+                for actor_id in actor_ids {
+                    let actor_name = self
+                        .actor_name
+                        .get(actor_id)
+                        .map_or("unknown", |name| name.as_str());
+                    actor_names.entry(actor_name).or_default().insert(*actor_id);
+                }
+                (io_info.as_str(), actor_names)
+            })
+            .collect()
+    }
+
+    /// Whether any bottleneck was detected, i.e. anything besides the statistics is reported.
+    pub(crate) fn bottleneck_found(&self) -> bool {
+        !self.findings.is_empty() || !self.wait_cycles.is_empty()
     }
 
     pub fn merge_other(&mut self, b: &AnalyzeSummary) {
         self.total_actors_analyzed += b.total_actors_analyzed;
         self.has_fast_children_actors
             .extend(b.has_fast_children_actors.clone());
-        self.io_bound_actors.extend(b.io_bound_actors.clone());
+        for (io_info, actor_ids) in &b.io_bound_actors {
+            self.io_bound_actors
+                .entry(io_info.clone())
+                .or_default()
+                .extend(actor_ids);
+        }
+        self.join_amplifications
+            .extend(b.join_amplifications.iter().cloned());
+        self.wait_cycles.extend(b.wait_cycles.iter().cloned());
+        self.findings.extend(b.findings.iter().cloned());
+        self.current_awaits.merge(&b.current_awaits);
+        self.actor_elapsed_ns.extend(&b.actor_elapsed_ns);
+        self.epoch_elapsed_ns.extend(&b.epoch_elapsed_ns);
+        for (kind, stats) in &b.span_stats {
+            self.span_stats
+                .entry(kind.clone())
+                .or_default()
+                .merge(stats);
+        }
+        self.actor_name.extend(b.actor_name.clone());
+        sort_findings(&mut self.findings);
     }
 }
 
@@ -97,67 +446,148 @@ impl Default for AnalyzeSummary {
     }
 }
 
+/// Elapsed time statistics of an [`AnalyzeSummary`], without the findings.
+///
+/// Created by [`AnalyzeSummary::stats`].
+pub struct StatsReport<'a> {
+    summary: &'a AnalyzeSummary,
+}
+
+impl AnalyzeSummary {
+    pub fn stats(&self) -> StatsReport<'_> {
+        StatsReport { summary: self }
+    }
+}
+
+impl Display for StatsReport<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let summary = self.summary;
+        if !summary.actor_elapsed_ns.is_empty() {
+            writeln!(f, "\n--- Actor Elapsed Time Distribution ---")?;
+            write!(f, "{}", summary.actor_elapsed())?;
+        }
+        if !summary.epoch_elapsed_ns.is_empty() {
+            writeln!(f, "\n--- Epoch Elapsed Time Distribution ---")?;
+            write!(f, "{}", summary.epoch_elapsed())?;
+        }
+
+        if !summary.span_stats.is_empty() {
+            writeln!(
+                f,
+                "\n--- Top {} Slowest Span Kinds ---",
+                TOP_SPAN_KINDS_LIMIT.min(summary.span_stats.len())
+            )?;
+            for (kind, stats) in summary.top_span_kinds(TOP_SPAN_KINDS_LIMIT) {
+                writeln!(f, "{}: {}", kind, stats)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Display for AnalyzeSummary {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "------ Analyze Summary ------")?;
         writeln!(f, "Total actors analyzed: {}", self.total_actors_analyzed)?;
+        writeln!(f, "Slow span threshold: {}", self.slow_threshold)?;
 
-        if !self.actor_elapsed_ns.is_empty() {
-            // TODO: can (should) we add sth like histogram?
-            writeln!(f, "\n--- Actor Elapsed Time Distribution ---")?;
+        write!(f, "{}", self.stats())?;
 
-            let count = self.actor_elapsed_ns.len();
-            let min = self.actor_elapsed_ns.first().unwrap().0;
-            let max = self.actor_elapsed_ns.last().unwrap().0;
+        if !self.current_awaits.actors.is_empty() {
+            writeln!(f, "\n--- Current Await Points ---")?;
+            write!(f, "{}", self.current_awaits)?;
+        }
 
-            writeln!(f, "Count: {}", count)?;
-            writeln!(
-                f,
-                "Min: {:.3}s",
-                Duration::from_nanos(min as u64).as_secs_f64()
-            )?;
-            writeln!(
-                f,
-                "Max: {:.3}s",
-                Duration::from_nanos(max as u64).as_secs_f64()
-            )?;
+        if !self.wait_cycles.is_empty() {
+            writeln!(f, "\n\n--- Wait-for Cycles ---")?;
+            for cycle in &self.wait_cycles {
+                writeln!(
+                    f,
+                    ">> Cycle: {}",
+                    cycle
+                        .iter()
+                        .map(|edge| format!("Actor {}", edge.downstream))
+                        .chain(
+                            cycle
+                                .first()
+                                .map(|edge| format!("Actor {}", edge.downstream))
+                        )
+                        .join(" -> ")
+                )?;
+                for edge in cycle {
+                    writeln!(f, "  {}", edge)?;
+                }
+            }
         }
 
-        let mut bottleneck_actors_found = false;
+        if !self.findings.is_empty() {
+            writeln!(f, "\n\n--- Ranked Findings ---")?;
+            for (rank, finding) in self.findings.iter().take(RANKED_FINDINGS_LIMIT).enumerate() {
+                writeln!(f, "#{} {}", rank + 1, finding)?;
+                writeln!(f, "    Path: {}", finding.evidence)?;
+                writeln!(f, "    Children: {}", finding.evidence.children)?;
+                for hint in &finding.hints {
+                    writeln!(f, "    Hint: {}", hint)?;
+                    writeln!(f, "      Remediation: {}", hint.remediation)?;
+                }
+            }
+            if self.findings.len() > RANKED_FINDINGS_LIMIT {
+                writeln!(
+                    f,
+                    "... and {} more findings",
+                    self.findings.len() - RANKED_FINDINGS_LIMIT
+                )?;
+            }
+        }
 
         if !self.has_fast_children_actors.is_empty() {
             writeln!(f, "\n\n--- Fast Children Actors ---")?;
-            for (actor_id, tree) in &self.has_fast_children_actors {
-                writeln!(f, ">> Actor {}", actor_id)?;
-                writeln!(f, "{}", tree)?;
+            for (group, bottlenecks) in self.fast_children_groups() {
+                if group.actor_ids.len() == 1 {
+                    writeln!(f, ">> Actor {}", group.actor_ids.first().unwrap())?;
+                } else {
+                    writeln!(
+                        f,
+                        ">> {} actors: {:?}",
+                        group.actor_ids.len(),
+                        group.actor_ids
+                    )?;
+                }
+                let mut render = group
+                    .render()
+                    .options(self.render_options)
+                    .slow_threshold(self.slow_threshold.threshold_ns);
+                for path in bottlenecks {
+                    render = render.bottleneck(path);
+                }
+                writeln!(f, "{}", render)?;
+            }
+        }
+        if !self.join_amplifications.is_empty() {
+            writeln!(f, "\n\n--- Join Amplification ---")?;
+            for join in &self.join_amplifications {
+                writeln!(
+                    f,
+                    ">> Join: `{}` (operator {} of `{}`)",
+                    join.join_executor, join.operator_id, join.job_name
+                )?;
+                writeln!(f, "  Fragment actors: {:?}", join.fragment_actors)?;
+                writeln!(f, "  Upstream join actors: {:?}", join.upstream_actors)?;
+                writeln!(f, "  Downstream actors: {:?}", join.downstream_actors)?;
             }
-            bottleneck_actors_found = true;
         }
         if !self.io_bound_actors.is_empty() {
             writeln!(f, "\n\n--- IO Bound Actors ---")?;
-            for (io_info, actor_ids) in self.io_bound_actors.iter().sorted_by_key(|x| x.0) {
+            for (io_info, actor_names) in self.io_bound_groups() {
                 writeln!(f, ">> IO Info: `{}`", io_info)?;
-                let mut actor_names: BTreeMap<String, BTreeSet<u32>> = BTreeMap::new();
-                for actor_id in actor_ids {
-                    let actor_name = self
-                        .actor_name
-                        .get(actor_id)
-                        .cloned()
-                        .unwrap_or("unknown".to_string());
-                    actor_names
-                        .entry(actor_name.clone())
-                        .or_default()
-                        .insert(*actor_id);
-                }
                 writeln!(f, "  Actors:")?;
                 for (actor_name, actor_ids) in actor_names.iter() {
                     writeln!(f, "    {}: {:?}", actor_name, actor_ids)?;
                 }
             }
-            bottleneck_actors_found = true;
         }
 
-        if !bottleneck_actors_found {
+        if !self.bottleneck_found() {
             writeln!(f, "No bottleneck actors detected.")?;
         }
         Ok(())
@@ -168,7 +598,13 @@ impl TreeView {
     /// The target of this function is to analyze whether the current tree is the
     /// bottleneck.
     pub fn is_bottleneck(&self) -> bool {
-        self.has_fast_children() || self.is_io_bound()
+        self.is_bottleneck_with_threshold(SLOW_SPAN_NS)
+    }
+
+    /// Same as [`TreeView::is_bottleneck`], where spans pending for at least `slow_ns` are
+    /// slow.
+    pub fn is_bottleneck_with_threshold(&self, slow_ns: u128) -> bool {
+        self.has_fast_children(slow_ns) || self.is_io_bound(slow_ns)
     }
 
     /// This function checks if the tree contains the characteristic bottleneck pattern:
@@ -233,24 +669,24 @@ impl TreeView {
     /// In this case, although the bottleneck actor(BN Tree) throttles the whole graph,
     /// the bottleneck actor is still yielding output to downstream actors. A typical
     /// case is JOIN amplification. So the corresponding actors are actively processing
-    /// the data but the EPOCH span is blocked.
-    pub(crate) fn has_fast_children(&self) -> bool {
-        self.tree.visit(&|node| {
+    /// the data but the EPOCH span is blocked. Such trees are not reported by this rule;
+    /// see [`TreeView::amplified_upstreams`] for how they are traced back to the join.
+    pub(crate) fn has_fast_children(&self, slow_ns: u128) -> bool {
+        self.fast_children_span(slow_ns).is_some()
+    }
+
+    /// Returns the path and the node of the first span matching the pattern of
+    /// [`TreeView::has_fast_children`], where spans pending for at least `slow_ns` are slow.
+    pub(crate) fn fast_children_span(&self, slow_ns: u128) -> Option<(Vec<usize>, &SpanNodeView)> {
+        self.tree.iter_with_path().find(|(_, node)| {
             let elapsed_secs = node.elapsed_ns as f64 / 1_000_000_000.0;
-            let slow_span = !node.span.is_long_running && elapsed_secs >= 10.0;
+            let slow_span = node.is_slower_than(slow_ns);
             let is_epoch = node.span.name.starts_with("Epoch");
 
             if !is_epoch && !node.children.is_empty() {
                 // IB Tree's `Epoch` span may have a long elapsed time, though it's not
                 // a bottleneck. We exclude the `Epoch` span from the bottleneck detection
-                let mut elapsed_sum = 0.;
-                let mut elapsed_count = 0;
-                for child in &node.children {
-                    elapsed_count += 1;
-                    elapsed_sum += child.elapsed_ns as f64 / 1_000_000_000.0;
-                }
-                let elapsed_avg = elapsed_sum / elapsed_count as f64;
-                if slow_span && (elapsed_avg * 5.0 < elapsed_secs) {
+                if slow_span && (node.children_avg_secs() * 5.0 < elapsed_secs) {
                     return true;
                 }
             }
@@ -258,10 +694,8 @@ impl TreeView {
         })
     }
 
-    fn is_io_bound(&self) -> bool {
-        let mut map = HashMap::new();
-        self.find_io_bound(0, &mut map);
-        !map.is_empty()
+    fn is_io_bound(&self, slow_ns: u128) -> bool {
+        self.io_bound_spans(slow_ns).next().is_some()
     }
 
     /// This function checks if the tree contains the characteristic bottleneck pattern:
@@ -272,22 +706,218 @@ impl TreeView {
     pub(crate) fn find_io_bound(
         &self,
         actor_id: u32,
-        io_bound_actors: &mut HashMap<IoInfo, HashSet<u32>>,
+        io_bound_actors: &mut BTreeMap<IoInfo, BTreeSet<u32>>,
+        slow_ns: u128,
     ) {
-        self.tree.visit_all(&mut |node| {
-            let elapsed_secs = node.elapsed_ns as f64 / 1_000_000_000.0;
-            let slow_span = !node.span.is_long_running && elapsed_secs >= 10.0;
+        for (_, node) in self.io_bound_spans(slow_ns) {
+            io_bound_actors
+                .entry(node.span.name.clone())
+                .or_default()
+                .insert(actor_id);
+        }
+    }
+
+    /// Returns the paths and the nodes of the storage operation spans of the tree pending for
+    /// at least `slow_ns`.
+    pub(crate) fn io_bound_spans(
+        &self,
+        slow_ns: u128,
+    ) -> impl Iterator<Item = (Vec<usize>, &SpanNodeView)> {
+        self.tree.iter_with_path().filter(move |(_, node)| {
             let is_io_operation =
                 node.span.name.starts_with("store_") || node.span.name.contains("fetch_block");
+            is_io_operation && node.is_slower_than(slow_ns)
+        })
+    }
 
-            if is_io_operation && slow_span {
-                io_bound_actors
-                    .entry(node.span.name.clone())
-                    .or_default()
-                    .insert(actor_id);
+    /// Returns the upstream actors feeding this tree if it is the special IB Tree described in
+    /// [`TreeView::has_fast_children`]: the `Epoch` span is slow, but its children, down to the
+    /// exchange inputs, are repeatedly fast because the upstream keeps yielding output. The
+    /// `Epoch` span is slow if it is pending for at least `slow_ns`.
+    pub(crate) fn amplified_upstreams(&self, slow_ns: u128) -> BTreeSet<u32> {
+        let mut upstreams = BTreeSet::new();
+        for epoch in &self.tree.children {
+            if !epoch.span.name.starts_with("Epoch") || !epoch.is_slower_than(slow_ns) {
+                continue;
+            }
+            if epoch.children.is_empty()
+                || epoch
+                    .children
+                    .iter()
+                    .any(|child| child.elapsed_ns * 5 >= epoch.elapsed_ns)
+            {
+                continue;
+            }
+            epoch.visit_all(&mut |node| {
+                if let Some(upstream) = upstream_actor_id(&node.span.name) {
+                    upstreams.insert(upstream);
+                }
+            });
+        }
+        upstreams
+    }
+
+    /// Returns the first join executor span in the tree, e.g. `HashJoin 1500000003`.
+    pub(crate) fn find_join_executor(&self) -> Option<(Vec<usize>, &SpanNodeView, u32)> {
+        self.tree
+            .iter_with_path()
+            .find_map(|(path, node)| match parse_executor(&node.span.name) {
+                Some((kind, operator_id)) if kind.ends_with("Join") => {
+                    Some((path, node, operator_id))
+                }
+                _ => None,
+            })
+    }
+
+    /// Returns the age of the oldest `Epoch` span, i.e. how long the actor has been processing
+    /// its oldest in-flight epoch.
+    pub(crate) fn epoch_age_ns(&self) -> Option<u128> {
+        self.tree
+            .children
+            .iter()
+            .filter(|node| node.span.name.starts_with("Epoch"))
+            .map(|node| node.elapsed_ns)
+            .max()
+    }
+
+    /// Returns the executors of the main tree as `(type, operator id)`, which are the same for
+    /// all actors of a fragment.
+    pub(crate) fn executor_fingerprint(&self) -> BTreeSet<(&str, u32)> {
+        self.tree
+            .iter()
+            .filter_map(|node| parse_executor(&node.span.name))
+            .collect()
+    }
+
+    /// Returns the job name quoted in the root span, e.g. `mv` in "Actor 1: `mv`".
+    pub(crate) fn job_name(&self) -> Option<&str> {
+        self.tree.span.name.split('`').nth(1)
+    }
+}
+
+/// Finds join amplification by linking the special IB Trees to their upstream actors and
+/// checking whether those run a join executor. Findings are grouped by join fragment.
+fn find_join_amplifications(
+    trees: &BTreeMap<u32, TreeView>,
+    slow_ns: u128,
+) -> Vec<JoinAmplification> {
+    let mut joins: BTreeMap<(String, u32), JoinAmplification> = BTreeMap::new();
+    for (actor_id, tree) in trees {
+        for upstream in tree.amplified_upstreams(slow_ns) {
+            let Some(upstream_tree) = trees.get(&upstream) else {
+                continue;
+            };
+            let Some((_, join_node, operator_id)) = upstream_tree.find_join_executor() else {
+                continue;
+            };
+            let job_name = upstream_tree.job_name().unwrap_or("unknown").to_owned();
+            let join = joins
+                .entry((job_name.clone(), operator_id))
+                .or_insert_with(|| JoinAmplification {
+                    job_name,
+                    join_executor: join_node.span.name.clone(),
+                    operator_id,
+                    fragment_actors: BTreeSet::new(),
+                    upstream_actors: BTreeSet::new(),
+                    downstream_actors: BTreeSet::new(),
+                });
+            join.upstream_actors.insert(upstream);
+            join.downstream_actors.insert(*actor_id);
+        }
+    }
+
+    for join in joins.values_mut() {
+        join.fragment_actors = trees
+            .iter()
+            .filter(|(_, tree)| {
+                tree.job_name() == Some(join.job_name.as_str())
+                    && tree
+                        .find_join_executor()
+                        .is_some_and(|(_, _, operator_id)| operator_id == join.operator_id)
+            })
+            .map(|(actor_id, _)| *actor_id)
+            .collect();
+    }
+    joins.into_values().collect()
+}
+
+/// Finds the actors with an executor much slower than the same executor of their sibling
+/// actors, grouped by job name and [`TreeView::executor_fingerprint`]. Among the executors of
+/// an actor pending for at least `slow_ns`, the deepest one is reported, as its ancestors wait
+/// for it. Exchange executors such as `Merge` are skipped, as a slow one means the actor is
+/// starved by its upstream rather than skewed.
+fn find_stragglers(trees: &BTreeMap<u32, TreeView>, slow_ns: u128) -> Vec<Finding> {
+    let mut fragments: BTreeMap<_, Vec<(u32, &TreeView)>> = BTreeMap::new();
+    for (actor_id, tree) in trees {
+        let fingerprint = tree.executor_fingerprint();
+        if !fingerprint.is_empty() {
+            fragments
+                .entry((tree.job_name(), fingerprint))
+                .or_default()
+                .push((*actor_id, tree));
+        }
+    }
+
+    let mut findings = vec![];
+    for actors in fragments.values() {
+        if actors.len() < MIN_STRAGGLER_SIBLINGS {
+            continue;
+        }
+        // operator id -> (elapsed_ns, actor_id) of the executor in each actor
+        let mut executors: BTreeMap<u32, BTreeSet<(u128, u32)>> = BTreeMap::new();
+        let mut paths: BTreeMap<(u32, u32), (Vec<usize>, bool)> = BTreeMap::new();
+        for (actor_id, tree) in actors {
+            for (path, node) in tree.tree.iter_with_path() {
+                if let Some((kind, operator_id)) = parse_executor(&node.span.name) {
+                    if EXCHANGE_SPAN_KINDS.contains(&kind) {
+                        continue;
+                    }
+                    executors
+                        .entry(operator_id)
+                        .or_default()
+                        .insert((node.elapsed_ns, *actor_id));
+                    paths.insert(
+                        (*actor_id, operator_id),
+                        (path, node.is_slower_than(slow_ns)),
+                    );
+                }
+            }
+        }
+
+        // actor id -> (path, sibling median) of the deepest straggling executor
+        let mut stragglers: BTreeMap<u32, (Vec<usize>, u128)> = BTreeMap::new();
+        for (operator_id, values) in &executors {
+            let distribution = Distribution::new(values);
+            let Some(median_ns) = distribution.percentile(50.0) else {
+                continue;
+            };
+            for (elapsed_ns, actor_id) in distribution.outliers() {
+                let (path, is_slow) = &paths[&(actor_id, *operator_id)];
+                if elapsed_ns <= median_ns || !is_slow {
+                    continue;
+                }
+                let straggler = stragglers
+                    .entry(actor_id)
+                    .or_insert_with(|| (path.clone(), median_ns));
+                if path.len() > straggler.0.len() {
+                    *straggler = (path.clone(), median_ns);
+                }
             }
-        });
+        }
+
+        for (actor_id, (path, median_ns)) in stragglers {
+            let mut finding = Finding::new(
+                actor_id,
+                FindingKind::Straggler,
+                &trees[&actor_id],
+                path,
+                slow_ns,
+            );
+            finding.sibling_median_ns = Some(median_ns);
+            findings.push(finding);
+        }
     }
+    findings
 }
 
 pub fn bottleneck_detect_from_file(path: &str) -> anyhow::Result<AnalyzeSummary> {
@@ -297,3 +927,305 @@ pub fn bottleneck_detect_from_file(path: &str) -> anyhow::Result<AnalyzeSummary>
         .map_err(|e| anyhow::anyhow!("Failed to extract actor traces from file: {}", e))?;
     AnalyzeSummary::from_traces(&actor_traces)
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use crate::await_tree::utils::extract_actor_traces;
+    use crate::await_tree::{
+        AnalyzeConfig, AnalyzeSummary, FindingKind, Severity, SlowThreshold, TreeView, Verdict,
+        SLOW_SPAN_NS,
+    };
+
+    #[test]
+    fn test_findings_ranked_by_score() -> Result<()> {
+        let io_bound = r#"Actor 1: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 12.000s]
+    Materialize 100000007 [!!! 12.000s]
+      store_flush [!!! 12.000s]
+"#;
+        let bottleneck = r#"Actor 2: `mv` [1000.000s]
+  Epoch 8251479171792896 [!!! 900.000s]
+    Materialize 200000007 [!!! 900.000s]
+      HashAgg 200000005 [!!! 900.000s]
+        Merge 200000004 [0.001s]
+"#;
+        let trees = BTreeMap::from([
+            (1, TreeView::from_str(io_bound).unwrap()),
+            (2, TreeView::from_str(bottleneck).unwrap()),
+        ]);
+        let summary = AnalyzeSummary::from_trees(&trees);
+
+        let findings = summary.findings();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].actor_id, 2);
+        assert_eq!(findings[0].kind, FindingKind::FastChildren);
+        assert_eq!(findings[0].span, "HashAgg 200000005");
+        assert_eq!(findings[1].kind, FindingKind::IoBound);
+        assert!(findings[0].score > findings[1].score);
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_amplification() -> Result<()> {
+        let join = r#"Actor 21: `mv` [100.000s]
+  Epoch 8318328768692224 [!!! 95.000s]
+    HashJoin 1500000003 [!!! 95.000s]
+      hash_join_barrier_align [!!! 95.000s]
+        Merge 1500000001 [!!! 95.000s]
+          LocalInput (actor 5) [!!! 95.000s]
+"#;
+        let sibling = r#"Actor 23: `mv` [100.000s]
+  Epoch 8318328768692224 [!!! 95.000s]
+    HashJoin 1700000003 [!!! 95.000s]
+      Merge 1700000001 [!!! 95.000s]
+"#;
+        let downstream = r#"Actor 30: `mv` [100.000s]
+  Epoch 8318328637423616 [!!! 90.000s]
+    Union 1E00000007 [1.000s]
+      Merge 1E00000003 [1.000s]
+        LocalInput (actor 21) [1.000s]
+"#;
+        let trees = BTreeMap::from([
+            (21, TreeView::from_str(join).unwrap()),
+            (23, TreeView::from_str(sibling).unwrap()),
+            (30, TreeView::from_str(downstream).unwrap()),
+        ]);
+        let summary = AnalyzeSummary::from_trees(&trees);
+
+        assert_eq!(summary.join_amplifications.len(), 1);
+        let join = &summary.join_amplifications[0];
+        assert_eq!(join.join_executor, "HashJoin 1500000003");
+        assert_eq!(join.job_name, "mv");
+        assert_eq!(
+            join.fragment_actors.iter().copied().collect::<Vec<_>>(),
+            [21, 23]
+        );
+        assert_eq!(
+            join.upstream_actors.iter().copied().collect::<Vec<_>>(),
+            [21]
+        );
+        assert_eq!(
+            join.downstream_actors.iter().copied().collect::<Vec<_>>(),
+            [30]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_straggler() -> Result<()> {
+        let actor = |actor_id: u32, elapsed: &str| {
+            TreeView::from_str(&format!(
+                r#"Actor {actor_id}: `mv` [1000.000s]
+  Epoch 8251479171792896 [{elapsed}]
+    Materialize {actor_id}00000007 [{elapsed}]
+      HashAgg {actor_id}00000005 [{elapsed}]
+        Merge {actor_id}00000004 [{elapsed}]
+"#
+            ))
+            .unwrap()
+        };
+        let trees = BTreeMap::from([
+            (1, actor(1, "1.000s")),
+            (2, actor(2, "1.200s")),
+            (3, actor(3, "0.800s")),
+            (4, actor(4, "!!! 600.000s")),
+        ]);
+        let summary = AnalyzeSummary::from_trees(&trees);
+
+        let stragglers = summary
+            .findings()
+            .iter()
+            .filter(|finding| finding.kind == FindingKind::Straggler)
+            .collect::<Vec<_>>();
+        assert_eq!(stragglers.len(), 1);
+        assert_eq!(stragglers[0].actor_id, 4);
+        assert_eq!(stragglers[0].span, "HashAgg 400000005");
+        assert_eq!(stragglers[0].sibling_median_ns, Some(1_000_000_000));
+
+        // A straggler alone is reported as a bottleneck.
+        assert_eq!(summary.findings().len(), 1);
+        assert!(summary.bottleneck_found());
+        assert!(!summary
+            .to_string()
+            .contains("No bottleneck actors detected."));
+        assert!(!summary
+            .markdown()
+            .to_string()
+            .contains("No bottleneck actors detected."));
+
+        // Stragglers are slow by the threshold of the analysis.
+        let trees = BTreeMap::from([
+            (1, actor(1, "1.000s")),
+            (2, actor(2, "1.200s")),
+            (3, actor(3, "0.800s")),
+            (4, actor(4, "6.000s")),
+        ]);
+        let is_straggler = |summary: &AnalyzeSummary| {
+            summary
+                .findings()
+                .iter()
+                .any(|finding| finding.kind == FindingKind::Straggler)
+        };
+        assert!(!is_straggler(&AnalyzeSummary::from_trees(&trees)));
+        let config = AnalyzeConfig {
+            slow_threshold: SlowThreshold::Fixed(Duration::from_secs(2)),
+            ..Default::default()
+        };
+        assert!(is_straggler(&AnalyzeSummary::from_trees_with_config(
+            &trees, &config
+        )));
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_span_kinds() -> Result<()> {
+        let actor = |actor_id: u32, agg: &str, input: &str| {
+            TreeView::from_str(&format!(
+                r#"Actor {actor_id}: `mv` [5.000s]
+  Epoch {actor_id} [4.000s]
+    HashAgg {actor_id}00000005 [{agg}]
+      Merge {actor_id}00000004 [1.000s]
+        LocalInput (actor 9) [{input}]
+"#
+            ))
+            .unwrap()
+        };
+        let trees = BTreeMap::from([
+            (1, actor(1, "3.000s", "1.000s")),
+            (2, actor(2, "2.000s", "0.500s")),
+        ]);
+        let summary = AnalyzeSummary::from_trees(&trees);
+
+        let top = summary.top_span_kinds(10);
+        // Ordered by the maximum, then by the total elapsed time.
+        assert_eq!(
+            top.iter().map(|(kind, _)| *kind).collect::<Vec<_>>(),
+            ["Actor", "Epoch", "HashAgg", "Merge", "LocalInput"]
+        );
+        let (_, merge) = top[3];
+        assert_eq!(merge.count, 2);
+        assert_eq!(merge.total_ns, 2_000_000_000);
+        assert_eq!(merge.max_ns, 1_000_000_000);
+        assert_eq!(merge.actors.iter().copied().collect::<Vec<_>>(), [1, 2]);
+
+        assert_eq!(
+            summary
+                .top_span_kinds(2)
+                .iter()
+                .map(|(kind, _)| *kind)
+                .collect::<Vec<_>>(),
+            ["Actor", "Epoch"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_severity_follows_threshold() -> Result<()> {
+        let bottleneck = |secs: u32| {
+            TreeView::from_str(&format!(
+                r#"Actor 1: `mv` [1000.000s]
+  Epoch 8251479171792896 [!!! {secs}.000s]
+    Materialize 100000007 [!!! {secs}.000s]
+      HashAgg 100000005 [!!! {secs}.000s]
+        Merge 100000004 [0.{secs:03}s]
+"#
+            ))
+            .unwrap()
+        };
+        let analyze = |secs: u32, threshold_secs: u64| {
+            let trees = BTreeMap::from([(1, bottleneck(secs))]);
+            let config = AnalyzeConfig {
+                slow_threshold: SlowThreshold::Fixed(Duration::from_secs(threshold_secs)),
+                ..Default::default()
+            };
+            AnalyzeSummary::from_trees_with_config(&trees, &config).findings()[0].clone()
+        };
+        // 5x the threshold rates the same whatever the threshold.
+        let (default, lowered) = (analyze(50, 10), analyze(5, 1));
+        assert_eq!(default.severity, Severity::Medium);
+        assert_eq!(lowered.severity, Severity::Medium);
+        assert!(lowered.score > 0.0);
+        assert_eq!(lowered.score, default.score);
+        assert_eq!(analyze(50, 1).severity, Severity::High);
+        assert!(analyze(50, 1).score > default.score);
+        Ok(())
+    }
+
+    #[test]
+    fn test_adaptive_slow_threshold() -> Result<()> {
+        let healthy = |actor_id: u32| {
+            TreeView::from_str(&format!(
+                r#"Actor {actor_id}: `other_mv` [100.000s]
+  Epoch 8251479171792896 [1.000s]
+    Materialize {actor_id}00000007 [1.000s]
+      Merge {actor_id}00000004 [0.900s]
+"#
+            ))
+            .unwrap()
+        };
+        let bottleneck = r#"Actor 1: `mv` [100.000s]
+  Epoch 8251479171792896 [4.000s]
+    Materialize 100000007 [4.000s]
+      HashAgg 100000005 [4.000s]
+        Merge 100000004 [0.010s]
+"#;
+        let downstream = r#"Actor 5: `mv` [100.000s]
+  Epoch 8251479171792896 [3.000s]
+    Materialize 500000007 [3.000s]
+      Merge 500000004 [3.000s]
+        LocalInput (actor 1) [3.000s]
+"#;
+        let trees = BTreeMap::from([
+            (1, TreeView::from_str(bottleneck).unwrap()),
+            (2, healthy(2)),
+            (3, healthy(3)),
+            (4, healthy(4)),
+            (5, TreeView::from_str(downstream).unwrap()),
+        ]);
+        assert!(AnalyzeSummary::from_trees(&trees).findings().is_empty());
+
+        let config = AnalyzeConfig {
+            slow_threshold: SlowThreshold::adaptive(),
+            ..Default::default()
+        };
+        let summary = AnalyzeSummary::from_trees_with_config(&trees, &config);
+        assert_eq!(summary.slow_threshold_ns(), 2_000_000_000);
+        // Healthy actors are not slow by a threshold above the median epoch age.
+        assert_eq!(summary.findings().len(), 1);
+        assert_eq!(summary.findings()[0].span, "HashAgg 100000005");
+        // The blocked inputs follow the same threshold.
+        assert_eq!(summary.findings()[0].downstream_waiters, Some(1));
+        assert!(summary
+            .to_string()
+            .contains("Slow span threshold: 2.000s (adaptive: 2x the median epoch age 1.000s)"));
+
+        // A stalled cluster, where every epoch is old, is still reported.
+        for (path, verdict) in [
+            ("samples/dump_agg_bottleneck.txt", Verdict::Bottleneck),
+            ("samples/dump_io_bound.txt", Verdict::IoBound),
+        ] {
+            let traces = extract_actor_traces(&std::fs::read_to_string(path)?)?;
+            let summary = AnalyzeSummary::from_traces_with_config(&traces, &config)?;
+            assert_eq!(summary.slow_threshold_ns(), SLOW_SPAN_NS, "{}", path);
+            assert_eq!(summary.verdict(), verdict, "{}", path);
+        }
+
+        // Without epochs, equal elapsed times of one actor all count towards the median.
+        let no_epoch = r#"Actor 1: `mv` [100.000s]
+  Materialize 100000007 [8.000s]
+    Union 100000006 [4.000s]
+      Merge 100000004 [1.000s]
+      Merge 100000003 [1.000s]
+      Merge 100000002 [1.000s]
+"#;
+        let trees = BTreeMap::from([(1, TreeView::from_str(no_epoch).unwrap())]);
+        let summary = AnalyzeSummary::from_trees_with_config(&trees, &config);
+        assert_eq!(summary.slow_threshold_ns(), 2_000_000_000);
+        Ok(())
+    }
+}
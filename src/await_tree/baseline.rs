@@ -0,0 +1,339 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Comparison of a dump against a baseline taken from a healthy run of the same pipeline.
+//!
+//! Actor ids change across recoveries, so the baseline is keyed by job name and span kind.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::await_tree::stats::Distribution;
+use crate::await_tree::tree::TreeView;
+use crate::await_tree::utils::{normalize_span_name, parse_traces};
+
+/// A span kind is reported if its P90 elapsed time grows by at least this factor...
+const ELAPSED_REGRESSION_RATIO: f64 = 3.0;
+/// ...and by at least this many nanoseconds.
+const ELAPSED_REGRESSION_MIN_NS: u128 = 1_000_000_000;
+/// A current await kind is reported if its share of the job's actors changes by at least this
+/// much.
+const CURRENT_AWAIT_SHIFT: f64 = 0.25;
+
+/// Elapsed time statistics of one span kind of a job.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineSpanStats {
+    pub count: usize,
+    pub median_ns: u128,
+    pub p90_ns: u128,
+    pub max_ns: u128,
+}
+
+/// Statistics of a dump, keyed by job name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Job name -> number of actors
+    pub actor_counts: BTreeMap<String, usize>,
+    /// Job name -> span kind -> elapsed time statistics of the non-long-running spans
+    pub spans: BTreeMap<String, BTreeMap<String, BaselineSpanStats>>,
+    /// Job name -> kind of the current span -> number of actors, see
+    /// [`TreeView::current_await`].
+    pub current_awaits: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+impl Baseline {
+    pub fn new(trees: &BTreeMap<u32, TreeView>) -> Self {
+        let mut baseline = Self::default();
+        let mut elapsed: BTreeMap<String, BTreeMap<String, BTreeSet<(u128, u32)>>> =
+            BTreeMap::new();
+        // Spans of the same kind in one actor are told apart by their pre-order position.
+        let mut position = 0;
+        for tree in trees.values() {
+            let job_name = tree.job_name().unwrap_or("unknown").to_owned();
+            *baseline.actor_counts.entry(job_name.clone()).or_default() += 1;
+            if let Some(current) = tree.current_await() {
+                *baseline
+                    .current_awaits
+                    .entry(job_name.clone())
+                    .or_default()
+                    .entry(current.kind)
+                    .or_default() += 1;
+            }
+            let job_spans = elapsed.entry(job_name).or_default();
+            for node in tree.tree.iter().filter(|node| !node.span.is_long_running) {
+                position += 1;
+                job_spans
+                    .entry(normalize_span_name(&node.span.name))
+                    .or_default()
+                    .insert((node.elapsed_ns, position));
+            }
+        }
+        for (job_name, job_spans) in elapsed {
+            let stats = job_spans
+                .into_iter()
+                .map(|(kind, values)| {
+                    let distribution = Distribution::new(&values);
+                    let stats = BaselineSpanStats {
+                        count: distribution.len(),
+                        median_ns: distribution.percentile(50.0).unwrap_or(0),
+                        p90_ns: distribution.percentile(90.0).unwrap_or(0),
+                        max_ns: distribution.max().unwrap_or(0),
+                    };
+                    (kind, stats)
+                })
+                .collect();
+            baseline.spans.insert(job_name, stats);
+        }
+        baseline
+    }
+
+    /// Builds the baseline from the actor traces of a dump, as returned by
+    /// [`extract_actor_traces`](crate::await_tree::extract_actor_traces).
+    pub fn from_traces<'a, M>(actor_traces: M) -> anyhow::Result<Self>
+    where
+        M: IntoIterator<Item = (&'a u32, &'a String)>,
+    {
+        Ok(Self::new(&parse_traces(actor_traces)?))
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize baseline: {}", e))
+    }
+
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        serde_json::from_str(json).map_err(|e| anyhow::anyhow!("Failed to parse baseline: {}", e))
+    }
+
+    /// Compares the dump against this baseline.
+    pub fn compare(&self, trees: &BTreeMap<u32, TreeView>) -> BaselineComparison {
+        BaselineComparison::new(self, &Baseline::new(trees))
+    }
+}
+
+/// A span kind whose elapsed time grew significantly compared to the baseline.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpanRegression {
+    pub job_name: String,
+    pub kind: String,
+    /// `None` if the span kind is not in the baseline.
+    pub baseline: Option<BaselineSpanStats>,
+    pub current: BaselineSpanStats,
+}
+
+/// A current await kind whose share of the job's actors changed significantly.
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrentAwaitShift {
+    pub job_name: String,
+    pub kind: String,
+    pub baseline_share: f64,
+    pub current_share: f64,
+}
+
+/// Differences between a dump and a [`Baseline`].
+#[derive(Debug, Clone, Serialize)]
+pub struct BaselineComparison {
+    /// Jobs in the dump but not in the baseline.
+    pub new_jobs: BTreeSet<String>,
+    /// Jobs in the baseline but not in the dump.
+    pub missing_jobs: BTreeSet<String>,
+    pub span_regressions: Vec<SpanRegression>,
+    pub current_await_shifts: Vec<CurrentAwaitShift>,
+}
+
+impl BaselineComparison {
+    pub fn new(baseline: &Baseline, current: &Baseline) -> Self {
+        let new_jobs = current
+            .actor_counts
+            .keys()
+            .filter(|job_name| !baseline.actor_counts.contains_key(*job_name))
+            .cloned()
+            .collect();
+        let missing_jobs = baseline
+            .actor_counts
+            .keys()
+            .filter(|job_name| !current.actor_counts.contains_key(*job_name))
+            .cloned()
+            .collect();
+
+        let mut span_regressions = vec![];
+        for (job_name, job_spans) in &current.spans {
+            let Some(baseline_spans) = baseline.spans.get(job_name) else {
+                continue;
+            };
+            for (kind, stats) in job_spans {
+                let baseline_stats = baseline_spans.get(kind);
+                let baseline_p90_ns = baseline_stats.map_or(0, |stats| stats.p90_ns);
+                if stats.p90_ns >= baseline_p90_ns + ELAPSED_REGRESSION_MIN_NS
+                    && stats.p90_ns as f64 >= baseline_p90_ns as f64 * ELAPSED_REGRESSION_RATIO
+                {
+                    span_regressions.push(SpanRegression {
+                        job_name: job_name.clone(),
+                        kind: kind.clone(),
+                        baseline: baseline_stats.cloned(),
+                        current: stats.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut current_await_shifts = vec![];
+        for (job_name, awaits) in &current.current_awaits {
+            let Some(baseline_awaits) = baseline.current_awaits.get(job_name) else {
+                continue;
+            };
+            let share = |awaits: &BTreeMap<String, usize>, kind: &str| {
+                let total: usize = awaits.values().sum();
+                awaits.get(kind).copied().unwrap_or(0) as f64 / total.max(1) as f64
+            };
+            let kinds: BTreeSet<&String> = awaits.keys().chain(baseline_awaits.keys()).collect();
+            for kind in kinds {
+                let baseline_share = share(baseline_awaits, kind);
+                let current_share = share(awaits, kind);
+                if (current_share - baseline_share).abs() >= CURRENT_AWAIT_SHIFT {
+                    current_await_shifts.push(CurrentAwaitShift {
+                        job_name: job_name.clone(),
+                        kind: kind.clone(),
+                        baseline_share,
+                        current_share,
+                    });
+                }
+            }
+        }
+
+        Self {
+            new_jobs,
+            missing_jobs,
+            span_regressions,
+            current_await_shifts,
+        }
+    }
+
+    /// Whether the dump does not differ significantly from the baseline.
+    pub fn is_empty(&self) -> bool {
+        self.new_jobs.is_empty()
+            && self.missing_jobs.is_empty()
+            && self.span_regressions.is_empty()
+            && self.current_await_shifts.is_empty()
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize baseline comparison: {}", e))
+    }
+}
+
+impl Display for BaselineComparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "------ Baseline Comparison ------")?;
+        if self.is_empty() {
+            return writeln!(f, "No significant difference from the baseline.");
+        }
+        if !self.new_jobs.is_empty() {
+            writeln!(f, "New jobs: {:?}", self.new_jobs)?;
+        }
+        if !self.missing_jobs.is_empty() {
+            writeln!(f, "Missing jobs: {:?}", self.missing_jobs)?;
+        }
+
+        let secs = |ns: u128| ns as f64 / 1_000_000_000.0;
+        if !self.span_regressions.is_empty() {
+            writeln!(f, "\n--- Span Regressions ---")?;
+            for regression in &self.span_regressions {
+                write!(
+                    f,
+                    "`{}` {}: P90 {:.3}s",
+                    regression.job_name,
+                    regression.kind,
+                    secs(regression.current.p90_ns)
+                )?;
+                match &regression.baseline {
+                    Some(baseline) => writeln!(
+                        f,
+                        " vs {:.3}s in the baseline, max {:.3}s vs {:.3}s",
+                        secs(baseline.p90_ns),
+                        secs(regression.current.max_ns),
+                        secs(baseline.max_ns)
+                    )?,
+                    None => writeln!(f, ", not in the baseline")?,
+                }
+            }
+        }
+        if !self.current_await_shifts.is_empty() {
+            writeln!(f, "\n--- Current Await Shifts ---")?;
+            for shift in &self.current_await_shifts {
+                writeln!(
+                    f,
+                    "`{}` {}: {:.0}% of the actors vs {:.0}% in the baseline",
+                    shift.job_name,
+                    shift.kind,
+                    shift.current_share * 100.0,
+                    shift.baseline_share * 100.0
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    use crate::await_tree::{Baseline, TreeView};
+
+    #[test]
+    fn test_compare_with_baseline() -> Result<()> {
+        let actor = |actor_id: u32, elapsed: &str, current: &str| {
+            TreeView::from_str(&format!(
+                r#"Actor {actor_id}: `mv` [100.000s]
+  Epoch 8251479171792896 [{elapsed}]
+    HashAgg {actor_id}00000005 [{elapsed}]
+      Merge {actor_id}00000004 [0.100s]{current}
+"#
+            ))
+            .unwrap()
+        };
+        let healthy = BTreeMap::from([
+            (1, actor(1, "0.500s", "  <== current")),
+            (2, actor(2, "0.600s", "  <== current")),
+        ]);
+        let baseline = Baseline::from_json(&Baseline::new(&healthy).to_json()?)?;
+        assert_eq!(baseline, Baseline::new(&healthy));
+
+        // actor ids changed after a recovery
+        let stuck = BTreeMap::from([
+            (3, actor(3, "!!! 30.000s", "")),
+            (4, actor(4, "!!! 31.000s", "")),
+        ]);
+        let comparison = baseline.compare(&stuck);
+
+        assert!(comparison.new_jobs.is_empty());
+        assert_eq!(
+            comparison
+                .span_regressions
+                .iter()
+                .map(|regression| regression.kind.as_str())
+                .collect::<Vec<_>>(),
+            ["Epoch", "HashAgg"]
+        );
+        assert_eq!(comparison.current_await_shifts.len(), 2);
+        assert!(baseline.compare(&healthy).is_empty());
+        Ok(())
+    }
+}
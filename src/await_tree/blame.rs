@@ -0,0 +1,223 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use crate::await_tree::graph::{ActorGraph, InputEdge};
+use crate::await_tree::tree::{TreeView, SLOW_SPAN_NS};
+use crate::await_tree::utils::{format_span_path, parse_traces};
+
+/// Why a [`BlameChain`] stops at its last actor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainEnd {
+    /// The last actor is not blocked on any input, so it is holding the chain back.
+    Culprit {
+        /// Span names from the root span down to the deepest slow span of the actor, if any.
+        span_path: Option<Vec<String>>,
+    },
+    /// The last actor is blocked on an upstream actor missing from the dump, e.g. on
+    /// another compute node.
+    MissingUpstream { upstream: u32 },
+    /// The last actor is blocked on an actor already in the chain.
+    Cycle { upstream: u32 },
+}
+
+/// Chain of actors from a given actor up to the one holding it back, following the blocked
+/// exchange inputs. This answers "why is this MV not progressing?".
+#[derive(Debug, Clone)]
+pub struct BlameChain {
+    pub actor_id: u32,
+    /// The blocked input followed at each hop. If an actor is blocked on several inputs, the
+    /// one blocked for the longest time is followed.
+    pub hops: Vec<InputEdge>,
+    pub end: ChainEnd,
+}
+
+impl BlameChain {
+    pub fn new(trees: &BTreeMap<u32, TreeView>, actor_id: u32) -> anyhow::Result<Self> {
+        Self::with_slow_threshold(trees, actor_id, SLOW_SPAN_NS)
+    }
+
+    /// Builds the chain following the inputs pending for at least `slow_ns`, e.g. with
+    /// [`AnalyzeSummary::slow_threshold_ns`](crate::await_tree::AnalyzeSummary::slow_threshold_ns).
+    pub fn with_slow_threshold(
+        trees: &BTreeMap<u32, TreeView>,
+        actor_id: u32,
+        slow_ns: u128,
+    ) -> anyhow::Result<Self> {
+        if !trees.contains_key(&actor_id) {
+            anyhow::bail!("Actor {} not found in the dump", actor_id);
+        }
+        let graph = ActorGraph::with_slow_threshold(trees, slow_ns);
+        let mut hops: Vec<InputEdge> = vec![];
+        let mut visited = HashSet::from([actor_id]);
+        let mut current = actor_id;
+        let end = loop {
+            let Some(edge) = graph
+                .blocked_inputs(current)
+                .max_by_key(|edge| edge.elapsed_ns)
+            else {
+                break ChainEnd::Culprit {
+                    span_path: trees[&current].deepest_slow_span(slow_ns),
+                };
+            };
+            hops.push(edge.clone());
+            if !trees.contains_key(&edge.upstream) {
+                break ChainEnd::MissingUpstream {
+                    upstream: edge.upstream,
+                };
+            }
+            if !visited.insert(edge.upstream) {
+                break ChainEnd::Cycle {
+                    upstream: edge.upstream,
+                };
+            }
+            current = edge.upstream;
+        };
+        Ok(Self {
+            actor_id,
+            hops,
+            end,
+        })
+    }
+
+    /// Builds the chain from the actor traces of a dump, as returned by
+    /// [`extract_actor_traces`](crate::await_tree::extract_actor_traces).
+    pub fn from_traces<'a, M>(actor_traces: M, actor_id: u32) -> anyhow::Result<Self>
+    where
+        M: IntoIterator<Item = (&'a u32, &'a String)>,
+    {
+        Self::new(&parse_traces(actor_traces)?, actor_id)
+    }
+
+    /// Returns the actors of the chain, from the given actor to the last one.
+    pub fn actors(&self) -> Vec<u32> {
+        std::iter::once(self.actor_id)
+            .chain(self.hops.iter().map(|edge| edge.upstream))
+            .collect()
+    }
+}
+
+impl Display for BlameChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "------ Blame Chain of Actor {} ------", self.actor_id)?;
+        writeln!(
+            f,
+            "{}",
+            self.actors()
+                .iter()
+                .map(|actor_id| format!("Actor {}", actor_id))
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        )?;
+        for edge in &self.hops {
+            writeln!(f, "  {}", edge)?;
+        }
+        let last = self.actors().last().copied().unwrap_or(self.actor_id);
+        match &self.end {
+            ChainEnd::Culprit { span_path } => {
+                write!(f, "Actor {} is not blocked on any input", last)?;
+                match span_path {
+                    Some(path) => writeln!(f, ", slow at: {}", format_span_path(path))?,
+                    None => writeln!(f, " and has no slow span")?,
+                }
+            }
+            ChainEnd::MissingUpstream { upstream } => {
+                writeln!(f, "Actor {} is not in the dump", upstream)?;
+            }
+            ChainEnd::Cycle { upstream } => {
+                writeln!(f, "Actor {} is already in the chain", upstream)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TreeView {
+    /// Returns the span names from the root span down to the deepest span pending for at least
+    /// `slow_ns`.
+    pub(crate) fn deepest_slow_span(&self, slow_ns: u128) -> Option<Vec<String>> {
+        self.tree
+            .iter_with_path()
+            .filter(|(_, node)| node.is_slower_than(slow_ns))
+            .max_by_key(|(path, _)| path.len())
+            .map(|(path, _)| self.span_names(&path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::HashMap;
+
+    use crate::await_tree::{BlameChain, ChainEnd};
+
+    #[test]
+    fn test_blame_chain() -> Result<()> {
+        let traces = HashMap::from([
+            (
+                3,
+                r#"Actor 3: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 60.000s]
+    Materialize 300000002 [!!! 60.000s]
+      Merge 300000001 [!!! 60.000s]
+        LocalInput (actor 2) [!!! 60.000s]
+"#
+                .to_owned(),
+            ),
+            (
+                2,
+                r#"Actor 2: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 60.000s]
+    HashJoin 200000003 [!!! 60.000s]
+      Merge 200000001 [0.001s]
+        LocalInput (actor 5) [0.001s]
+      Merge 200000002 [!!! 60.000s]
+        RemoteInput (actor 1) [!!! 60.000s]
+"#
+                .to_owned(),
+            ),
+            (
+                1,
+                r#"Actor 1: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 60.000s]
+    HashAgg 100000002 [!!! 60.000s]
+      store_get [!!! 60.000s]
+"#
+                .to_owned(),
+            ),
+        ]);
+        let chain = BlameChain::from_traces(&traces, 3)?;
+
+        assert_eq!(chain.actors(), [3, 2, 1]);
+        assert_eq!(
+            chain.hops[1].path.last().map(String::as_str),
+            Some("RemoteInput (actor 1)")
+        );
+        assert_eq!(
+            chain.end,
+            ChainEnd::Culprit {
+                span_path: Some(vec![
+                    "Actor 1: `mv`".to_owned(),
+                    "Epoch 8251479171792896".to_owned(),
+                    "HashAgg 100000002".to_owned(),
+                    "store_get".to_owned(),
+                ])
+            }
+        );
+        assert!(BlameChain::from_traces(&traces, 4).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use itertools::Itertools;
+
+use crate::await_tree::tree::TreeView;
+use crate::await_tree::utils::{format_span_path, normalize_span_name};
+
+/// Span kinds an actor is expected to be parked on when it waits for input or output.
+pub(crate) const EXCHANGE_SPAN_KINDS: &[&str] = &[
+    "Merge",
+    "Receiver",
+    "LocalInput",
+    "RemoteInput",
+    "LocalOutput",
+    "RemoteOutput",
+    "receive_barrier",
+];
+
+/// Kind of the current span of an actor that is not being polled, in which case the
+/// `current` span is the root span of the tree.
+pub const ROOT_AWAIT_KIND: &str = "<root>";
+
+/// What an actor is awaiting at the moment of the dump, i.e. its `current` span.
+#[derive(Debug, Clone)]
+pub struct CurrentAwait {
+    /// Span names from the root span down to the current span. For a span in a detached
+    /// subtree, the path starts from the detached root.
+    pub path: Vec<String>,
+    /// Kind of the current span, or [`ROOT_AWAIT_KIND`], see [`AnalyzeSummary::top_span_kinds`].
+    ///
+    /// [`AnalyzeSummary::top_span_kinds`]: crate::await_tree::AnalyzeSummary::top_span_kinds
+    pub kind: String,
+    pub elapsed_ns: u128,
+    pub is_leaf: bool,
+    pub is_detached: bool,
+}
+
+impl CurrentAwait {
+    /// Whether the actor is parked on a leaf span other than an exchange, which is where it
+    /// is actually doing or waiting for something, e.g. a storage operation.
+    pub fn is_unexpected(&self) -> bool {
+        self.is_leaf
+            && !EXCHANGE_SPAN_KINDS.contains(&self.kind.as_str())
+            && !self.kind.starts_with("dispatch")
+    }
+}
+
+impl TreeView {
+    /// Resolves the `current` span id of the tree. Returns `None` if the span is not found,
+    /// e.g. in trees parsed from text without the `<== current` marker.
+    pub fn current_await(&self) -> Option<CurrentAwait> {
+        std::iter::once((&self.tree, false))
+            .chain(self.detached.iter().map(|root| (root, true)))
+            .find_map(|(root, is_detached)| {
+                let (path, node) = root
+                    .iter_with_path()
+                    .find(|(_, node)| node.id == self.current)?;
+                let is_root = path.is_empty() && !is_detached;
+                let mut names = vec![root.span.name.clone()];
+                let mut parent = root;
+                for index in path {
+                    parent = &parent.children[index];
+                    names.push(parent.span.name.clone());
+                }
+                Some(CurrentAwait {
+                    path: names,
+                    kind: if is_root {
+                        ROOT_AWAIT_KIND.to_owned()
+                    } else {
+                        normalize_span_name(&node.span.name)
+                    },
+                    elapsed_ns: node.elapsed_ns,
+                    is_leaf: !is_root && node.children.is_empty(),
+                    is_detached,
+                })
+            })
+    }
+}
+
+/// Report of what each actor is awaiting at the moment of the dump.
+#[derive(Debug, Clone, Default)]
+pub struct CurrentAwaitReport {
+    pub actors: BTreeMap<u32, CurrentAwait>,
+    /// Actors whose `current` span could not be resolved.
+    pub unresolved_actors: BTreeSet<u32>,
+}
+
+impl CurrentAwaitReport {
+    pub fn new(trees: &BTreeMap<u32, TreeView>) -> Self {
+        let mut report = Self::default();
+        for (actor_id, tree) in trees {
+            match tree.current_await() {
+                Some(current) => {
+                    report.actors.insert(*actor_id, current);
+                }
+                None => {
+                    report.unresolved_actors.insert(*actor_id);
+                }
+            }
+        }
+        report
+    }
+
+    pub(crate) fn merge(&mut self, other: &CurrentAwaitReport) {
+        self.actors.extend(other.actors.clone());
+        self.unresolved_actors.extend(&other.unresolved_actors);
+    }
+
+    /// Returns the actors currently parked on each span kind.
+    pub fn actors_by_kind(&self) -> BTreeMap<&str, BTreeSet<u32>> {
+        let mut by_kind: BTreeMap<&str, BTreeSet<u32>> = BTreeMap::new();
+        for (actor_id, current) in &self.actors {
+            by_kind
+                .entry(current.kind.as_str())
+                .or_default()
+                .insert(*actor_id);
+        }
+        by_kind
+    }
+
+    /// Returns the actors parked on an unexpected leaf span.
+    pub fn unexpected_actors(&self) -> impl Iterator<Item = (&u32, &CurrentAwait)> {
+        self.actors
+            .iter()
+            .filter(|(_, current)| current.is_unexpected())
+    }
+}
+
+impl Display for CurrentAwaitReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (kind, actor_ids) in self
+            .actors_by_kind()
+            .into_iter()
+            .sorted_by(|(a_kind, a), (b_kind, b)| b.len().cmp(&a.len()).then(a_kind.cmp(b_kind)))
+        {
+            writeln!(f, "{}: {} actors", kind, actor_ids.len())?;
+        }
+        if !self.unresolved_actors.is_empty() {
+            writeln!(f, "Unresolved: {:?}", self.unresolved_actors)?;
+        }
+
+        let unexpected = self.unexpected_actors().collect_vec();
+        if !unexpected.is_empty() {
+            writeln!(f, "Actors parked on unexpected leaf spans:")?;
+            for (actor_id, current) in unexpected {
+                writeln!(
+                    f,
+                    "  Actor {}: {}{} [{:.3}s]",
+                    actor_id,
+                    if current.is_detached {
+                        "[Detached] "
+                    } else {
+                        ""
+                    },
+                    format_span_path(&current.path),
+                    current.elapsed_ns as f64 / 1_000_000_000.0
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    use crate::await_tree::{CurrentAwaitReport, TreeView};
+
+    #[test]
+    fn test_current_await_report() -> Result<()> {
+        let io_bound = r#"Actor 1: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 12.000s]
+    Materialize 100000007 [!!! 12.000s]
+      store_get [!!! 12.000s]  <== current
+"#;
+        let waiting = r#"Actor 2: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 12.000s]
+    Materialize 200000007 [!!! 12.000s]
+      Merge 200000004 [!!! 12.000s]
+        LocalInput (actor 1) [!!! 12.000s]  <== current
+"#;
+        let idle = r#"Actor 3: `mv` [100.000s]
+"#;
+        let trees = BTreeMap::from([
+            (1, TreeView::from_str(io_bound).unwrap()),
+            (2, TreeView::from_str(waiting).unwrap()),
+            (3, TreeView::from_str(idle).unwrap()),
+        ]);
+        let report = CurrentAwaitReport::new(&trees);
+
+        let current = &report.actors[&1];
+        assert_eq!(current.kind, "store_get");
+        assert_eq!(current.path.len(), 4);
+        assert_eq!(report.actors[&2].kind, "LocalInput");
+        assert_eq!(
+            report
+                .unexpected_actors()
+                .map(|(actor_id, _)| *actor_id)
+                .collect::<Vec<_>>(),
+            [1]
+        );
+        assert_eq!(report.actors_by_kind().len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_detached_current_await() -> Result<()> {
+        let detached = r#"Actor 4: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 12.000s]
+[Detached 17]
+  fetch_block [!!! 12.000s]
+    store_get [!!! 12.000s]  <== current
+"#;
+        let trees = BTreeMap::from([(4, TreeView::from_str(detached).unwrap())]);
+        let report = CurrentAwaitReport::new(&trees);
+
+        let current = &report.actors[&4];
+        assert!(current.is_detached);
+        assert_eq!(current.path, ["fetch_block", "store_get"]);
+        assert_eq!(
+            report.to_string(),
+            "store_get: 1 actors\nActors parked on unexpected leaf spans:\n  Actor 4: [Detached] fetch_block > store_get [12.000s]\n"
+        );
+        Ok(())
+    }
+}
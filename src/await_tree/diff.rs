@@ -0,0 +1,511 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Actor-by-actor comparison of two dumps, e.g. before and after a config change or a
+//! recovery.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::await_tree::analyze::{AnalyzeConfig, AnalyzeSummary, SlowThreshold};
+use crate::await_tree::finding::{Finding, FindingKind};
+use crate::await_tree::series::{grew_by_gap, infer_gap_ns};
+use crate::await_tree::tree::TreeView;
+use crate::await_tree::utils::{format_span_path, parse_traces};
+
+/// A span present in both dumps.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunningSpan {
+    pub path: String,
+    pub before_ns: u128,
+    pub after_ns: u128,
+}
+
+/// Changes of an actor present in both dumps.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActorDiff {
+    /// The latest epoch the actor is processing in each dump.
+    pub epoch_before: Option<u64>,
+    pub epoch_after: Option<u64>,
+    /// Spans only present in the later dump.
+    pub new_spans: Vec<String>,
+    /// Spans only present in the earlier dump.
+    pub finished_spans: Vec<String>,
+    /// Spans present in both dumps whose elapsed time grew by about the gap between the dumps,
+    /// i.e. which have been running all along.
+    pub running_spans: Vec<RunningSpan>,
+}
+
+impl ActorDiff {
+    fn new(before: &TreeView, after: &TreeView, gap_ns: u128) -> Self {
+        let before_spans = before.spans_by_path();
+        let after_spans = after.spans_by_path();
+        let new_spans = after_spans
+            .keys()
+            .filter(|path| !before_spans.contains_key(*path))
+            .map(|path| format_span_path(path))
+            .collect();
+        let finished_spans = before_spans
+            .keys()
+            .filter(|path| !after_spans.contains_key(*path))
+            .map(|path| format_span_path(path))
+            .collect();
+        let running_spans = before_spans
+            .iter()
+            .filter_map(|(path, before)| {
+                let after = after_spans.get(path)?;
+                // Spans may be re-entered with the same name, e.g. `Merge`, in which case
+                // the elapsed time is reset.
+                grew_by_gap(before.elapsed_ns, after.elapsed_ns, gap_ns).then(|| RunningSpan {
+                    path: format_span_path(path),
+                    before_ns: before.elapsed_ns,
+                    after_ns: after.elapsed_ns,
+                })
+            })
+            .collect();
+        Self {
+            epoch_before: before.latest_epoch(),
+            epoch_after: after.latest_epoch(),
+            new_spans,
+            finished_spans,
+            running_spans,
+        }
+    }
+
+    /// Returns the running spans that have no running descendants, i.e. where the actor has
+    /// been waiting all along.
+    pub fn deepest_running_spans(&self) -> impl Iterator<Item = &RunningSpan> {
+        self.running_spans.iter().filter(|span| {
+            let prefix = format!("{} > ", span.path);
+            !self
+                .running_spans
+                .iter()
+                .any(|other| other.path.starts_with(&prefix))
+        })
+    }
+
+    pub fn epoch_advanced(&self) -> bool {
+        match (self.epoch_before, self.epoch_after) {
+            (Some(before), Some(after)) => after > before,
+            // The actor is idle in one of the dumps.
+            _ => self.epoch_before != self.epoch_after,
+        }
+    }
+}
+
+/// A finding identified independently of its score, which changes between dumps.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct FindingRef {
+    pub actor_id: u32,
+    pub kind: FindingKind,
+    pub span: String,
+}
+
+impl From<&Finding> for FindingRef {
+    fn from(finding: &Finding) -> Self {
+        Self {
+            actor_id: finding.actor_id,
+            kind: finding.kind.clone(),
+            span: finding.span.clone(),
+        }
+    }
+}
+
+/// Actor-by-actor comparison of two dumps.
+#[derive(Debug, Clone, Serialize)]
+pub struct DumpDiff {
+    /// Wall-clock gap between the dumps in nanoseconds.
+    pub gap_ns: u128,
+    /// Whether the gap was inferred from the growth of actor uptimes rather than given.
+    pub gap_inferred: bool,
+    pub actors_before: usize,
+    pub actors_after: usize,
+    pub appeared_actors: BTreeSet<u32>,
+    pub disappeared_actors: BTreeSet<u32>,
+    /// Actors present in both dumps.
+    pub actors: BTreeMap<u32, ActorDiff>,
+    /// Findings of the earlier dump that are gone in the later one.
+    pub resolved_findings: Vec<FindingRef>,
+    /// Findings of the later dump that are not in the earlier one.
+    pub introduced_findings: Vec<FindingRef>,
+}
+
+impl DumpDiff {
+    /// Compares the trees of two dumps, each keyed by actor id. The findings of both are
+    /// collected with `slow_threshold`.
+    ///
+    /// If `interval` is `None`, the wall-clock gap between the dumps is inferred as in
+    /// [`SeriesAnalysis::new`](crate::await_tree::SeriesAnalysis::new), which fails if no
+    /// actor is present in both.
+    pub fn new(
+        before: &BTreeMap<u32, TreeView>,
+        after: &BTreeMap<u32, TreeView>,
+        interval: Option<Duration>,
+        slow_threshold: SlowThreshold,
+    ) -> anyhow::Result<Self> {
+        let gap_ns = match interval {
+            Some(interval) => interval.as_nanos(),
+            None => infer_gap_ns(before, after).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Failed to infer the gap between the dumps: no actor is present in both, specify the interval"
+                )
+            })?,
+        };
+        let appeared_actors = after
+            .keys()
+            .filter(|actor_id| !before.contains_key(actor_id))
+            .copied()
+            .collect();
+        let disappeared_actors = before
+            .keys()
+            .filter(|actor_id| !after.contains_key(actor_id))
+            .copied()
+            .collect();
+        let actors = before
+            .iter()
+            .filter_map(|(actor_id, before)| {
+                let after = after.get(actor_id)?;
+                Some((*actor_id, ActorDiff::new(before, after, gap_ns)))
+            })
+            .collect();
+
+        let config = AnalyzeConfig {
+            slow_threshold,
+            ..Default::default()
+        };
+        let findings = |trees| -> BTreeSet<FindingRef> {
+            AnalyzeSummary::from_trees_with_config(trees, &config)
+                .findings()
+                .iter()
+                .map(FindingRef::from)
+                .collect()
+        };
+        let findings_before = findings(before);
+        let findings_after = findings(after);
+
+        Ok(Self {
+            gap_ns,
+            gap_inferred: interval.is_none(),
+            actors_before: before.len(),
+            actors_after: after.len(),
+            appeared_actors,
+            disappeared_actors,
+            actors,
+            resolved_findings: findings_before
+                .difference(&findings_after)
+                .cloned()
+                .collect(),
+            introduced_findings: findings_after
+                .difference(&findings_before)
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// Compares the actor traces of two dumps, as returned by
+    /// [`extract_actor_traces`](crate::await_tree::extract_actor_traces).
+    pub fn from_traces<'a, M>(
+        before: M,
+        after: M,
+        interval: Option<Duration>,
+        slow_threshold: SlowThreshold,
+    ) -> anyhow::Result<Self>
+    where
+        M: IntoIterator<Item = (&'a u32, &'a String)>,
+    {
+        Self::new(
+            &parse_traces(before)?,
+            &parse_traces(after)?,
+            interval,
+            slow_threshold,
+        )
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| anyhow::anyhow!("Failed to serialize dump diff: {}", e))
+    }
+}
+
+impl Display for DumpDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "------ Dump Diff ------")?;
+        writeln!(
+            f,
+            "Gap: {:.3}s{}",
+            self.gap_ns as f64 / 1_000_000_000.0,
+            if self.gap_inferred {
+                " (inferred from actor uptimes)"
+            } else {
+                ""
+            }
+        )?;
+        writeln!(
+            f,
+            "Actors: {} -> {} (+{}, -{})",
+            self.actors_before,
+            self.actors_after,
+            self.appeared_actors.len(),
+            self.disappeared_actors.len()
+        )?;
+        if !self.appeared_actors.is_empty() {
+            writeln!(f, "Appeared actors: {:?}", self.appeared_actors)?;
+        }
+        if !self.disappeared_actors.is_empty() {
+            writeln!(f, "Disappeared actors: {:?}", self.disappeared_actors)?;
+        }
+
+        let (advanced, not_advanced): (Vec<_>, Vec<_>) = self
+            .actors
+            .iter()
+            .partition(|(_, actor)| actor.epoch_advanced());
+        writeln!(
+            f,
+            "Epoch advanced: {} actors, not advanced: {} actors",
+            advanced.len(),
+            not_advanced.len()
+        )?;
+
+        if !not_advanced.is_empty() {
+            writeln!(f, "\n--- Actors Not Advancing ---")?;
+            for (actor_id, actor) in not_advanced {
+                match actor.epoch_after {
+                    Some(epoch) => writeln!(f, ">> Actor {} (epoch {})", actor_id, epoch)?,
+                    None => writeln!(f, ">> Actor {} (idle)", actor_id)?,
+                }
+                for span in actor.deepest_running_spans() {
+                    writeln!(
+                        f,
+                        "  still running: {} [{:.3}s -> {:.3}s]",
+                        span.path,
+                        span.before_ns as f64 / 1_000_000_000.0,
+                        span.after_ns as f64 / 1_000_000_000.0
+                    )?;
+                }
+                for path in &actor.new_spans {
+                    writeln!(f, "  new: {}", path)?;
+                }
+                for path in &actor.finished_spans {
+                    writeln!(f, "  finished: {}", path)?;
+                }
+            }
+        }
+
+        for (title, findings) in [
+            ("Resolved Findings", &self.resolved_findings),
+            ("Introduced Findings", &self.introduced_findings),
+        ] {
+            if !findings.is_empty() {
+                writeln!(f, "\n--- {} ---", title)?;
+                for finding in findings {
+                    writeln!(
+                        f,
+                        "{} at `{}` in actor {}",
+                        finding.kind, finding.span, finding.actor_id
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TreeView {
+    /// Returns the latest epoch the actor is processing, parsed from its `Epoch` spans.
+    pub(crate) fn latest_epoch(&self) -> Option<u64> {
+        self.tree
+            .children
+            .iter()
+            .filter_map(|node| node.span.name.strip_prefix("Epoch ")?.trim().parse().ok())
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use crate::await_tree::{DumpDiff, FindingKind, FindingRef, SlowThreshold, TreeView};
+
+    fn dump(trees: &[(u32, &str)]) -> BTreeMap<u32, TreeView> {
+        trees
+            .iter()
+            .map(|(actor_id, tree)| (*actor_id, TreeView::from_str(tree).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_dump_diff() -> Result<()> {
+        let before = dump(&[
+            (
+                1,
+                r#"Actor 1: `mv` [100.000s]
+  Epoch 100 [!!! 60.000s]
+    HashAgg 100000005 [!!! 60.000s]
+      Merge 100000004 [0.100s]
+"#,
+            ),
+            (
+                2,
+                r#"Actor 2: `mv` [100.000s]
+  Epoch 200 [!!! 20.000s]
+    Materialize 200000007 [!!! 20.000s]
+      store_flush [!!! 20.000s]
+"#,
+            ),
+            (
+                3,
+                r#"Actor 3: `mv` [100.000s]
+  Epoch 100 [1.000s]
+    Merge 300000004 [1.000s]
+"#,
+            ),
+        ]);
+        let after = dump(&[
+            (
+                1,
+                r#"Actor 1: `mv` [160.000s]
+  Epoch 100 [!!! 120.000s]
+    HashAgg 100000005 [!!! 120.000s]
+      store_get [0.200s]
+"#,
+            ),
+            (
+                2,
+                r#"Actor 2: `mv` [160.000s]
+  Epoch 300 [0.500s]
+    Materialize 200000007 [0.500s]
+"#,
+            ),
+            (
+                4,
+                r#"Actor 4: `mv` [40.000s]
+  Epoch 100 [!!! 30.000s]
+    HashAgg 400000005 [!!! 30.000s]
+      Merge 400000004 [0.010s]
+"#,
+            ),
+        ]);
+        let diff = DumpDiff::new(&before, &after, None, SlowThreshold::default())?;
+
+        assert_eq!((diff.gap_ns, diff.gap_inferred), (60_000_000_000, true));
+        assert_eq!((diff.actors_before, diff.actors_after), (3, 3));
+        assert_eq!(
+            diff.appeared_actors.iter().copied().collect::<Vec<_>>(),
+            [4]
+        );
+        assert_eq!(
+            diff.disappeared_actors.iter().copied().collect::<Vec<_>>(),
+            [3]
+        );
+        assert_eq!(diff.actors.keys().copied().collect::<Vec<_>>(), [1, 2]);
+
+        let stuck = &diff.actors[&1];
+        assert_eq!(
+            (stuck.epoch_before, stuck.epoch_after),
+            (Some(100), Some(100))
+        );
+        assert!(!stuck.epoch_advanced());
+        assert_eq!(
+            stuck.new_spans,
+            ["Actor 1 > Epoch 100 > HashAgg 100000005 > store_get"]
+        );
+        assert_eq!(
+            stuck.finished_spans,
+            ["Actor 1 > Epoch 100 > HashAgg 100000005 > Merge 100000004"]
+        );
+        let running = stuck.deepest_running_spans().collect::<Vec<_>>();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].path, "Actor 1 > Epoch 100 > HashAgg 100000005");
+        assert_eq!(
+            (running[0].before_ns, running[0].after_ns),
+            (60_000_000_000, 120_000_000_000)
+        );
+
+        let advanced = &diff.actors[&2];
+        assert_eq!(
+            (advanced.epoch_before, advanced.epoch_after),
+            (Some(200), Some(300))
+        );
+        assert!(advanced.epoch_advanced());
+        assert!(advanced
+            .finished_spans
+            .contains(&"Actor 2 > Epoch 200 > Materialize 200000007 > store_flush".to_owned()));
+
+        assert_eq!(
+            diff.resolved_findings,
+            [FindingRef {
+                actor_id: 2,
+                kind: FindingKind::IoBound,
+                span: "store_flush".to_owned(),
+            }]
+        );
+        assert_eq!(
+            diff.introduced_findings,
+            [FindingRef {
+                actor_id: 4,
+                kind: FindingKind::FastChildren,
+                span: "HashAgg 400000005".to_owned(),
+            }]
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&diff.to_json()?)?;
+        assert_eq!(json["appeared_actors"], serde_json::json!([4]));
+        assert_eq!(json["disappeared_actors"], serde_json::json!([3]));
+        assert_eq!(json["actors"]["1"]["epoch_after"], 100);
+        assert_eq!(json["resolved_findings"][0]["kind"], "io_bound");
+        assert_eq!(json["introduced_findings"][0]["kind"], "fast_children");
+
+        // A span that grew by less than the gap was restarted in between.
+        let diff = DumpDiff::new(
+            &before,
+            &after,
+            Some(Duration::from_secs(120)),
+            SlowThreshold::default(),
+        )?;
+        assert_eq!(diff.actors[&1].deepest_running_spans().count(), 0);
+
+        // Findings follow the given threshold.
+        let diff = DumpDiff::new(
+            &before,
+            &after,
+            None,
+            SlowThreshold::Fixed(Duration::from_secs(100)),
+        )?;
+        assert!(diff.resolved_findings.is_empty());
+        assert_eq!(
+            diff.introduced_findings,
+            [FindingRef {
+                actor_id: 1,
+                kind: FindingKind::FastChildren,
+                span: "HashAgg 100000005".to_owned(),
+            }]
+        );
+
+        assert!(DumpDiff::new(
+            &before,
+            &dump(&[(4, "Actor 4: `mv` [40.000s]")]),
+            None,
+            SlowThreshold::default()
+        )
+        .is_err());
+        Ok(())
+    }
+}
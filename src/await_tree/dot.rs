@@ -0,0 +1,232 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use itertools::Itertools;
+
+use crate::await_tree::analyze::AnalyzeSummary;
+use crate::await_tree::finding::FindingKind;
+use crate::await_tree::graph::ActorGraph;
+use crate::await_tree::tree::TreeView;
+
+/// State of an actor in the streaming graph, from the most to the least interesting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ActorState {
+    /// The actor has a bottleneck finding other than IO bound.
+    Bottleneck,
+    IoBound,
+    /// The actor is blocked on the input from an upstream actor.
+    InputBlocked,
+    /// The actor is blocked on sending its output to downstream actors.
+    OutputBlocked,
+    Running,
+}
+
+impl ActorState {
+    fn color(&self) -> &'static str {
+        match self {
+            ActorState::Bottleneck => "#f28b82",
+            ActorState::IoBound => "#fbbc04",
+            ActorState::InputBlocked => "#aecbfa",
+            ActorState::OutputBlocked => "#fdd663",
+            ActorState::Running => "#ffffff",
+        }
+    }
+}
+
+/// Graphviz DOT export of the actor graph of a dump, with actors grouped by job and fragment
+/// and colored by their [`ActorState`].
+///
+/// Created by [`AnalyzeSummary::dot`]. Edges follow the data flow from the upstream to the
+/// downstream actors, labelled with the elapsed time of the exchange input span. Render it
+/// with e.g. `dot -Tsvg graph.dot -o graph.svg`.
+pub struct DotExport<'a> {
+    summary: &'a AnalyzeSummary,
+    trees: &'a BTreeMap<u32, TreeView>,
+}
+
+/// Executors of the actors of a fragment, see [`TreeView::executor_fingerprint`].
+type Fingerprint<'a> = BTreeSet<(&'a str, u32)>;
+
+impl AnalyzeSummary {
+    /// Exports the graph of the trees the summary was built from as DOT.
+    pub fn dot<'a>(&'a self, trees: &'a BTreeMap<u32, TreeView>) -> DotExport<'a> {
+        DotExport {
+            summary: self,
+            trees,
+        }
+    }
+}
+
+fn escape(text: impl Display) -> String {
+    text.to_string()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl DotExport<'_> {
+    pub fn actor_state(&self, graph: &ActorGraph, actor_id: u32) -> ActorState {
+        let finding_state = self
+            .summary
+            .findings
+            .iter()
+            .filter(|finding| finding.actor_id == actor_id)
+            .map(|finding| match finding.kind {
+                FindingKind::IoBound => ActorState::IoBound,
+                _ => ActorState::Bottleneck,
+            })
+            .min();
+        if let Some(state) = finding_state {
+            return state;
+        }
+        if graph.blocked_inputs(actor_id).next().is_some() {
+            return ActorState::InputBlocked;
+        }
+        let output_blocked = self.trees.get(&actor_id).is_some_and(|tree| {
+            tree.tree.iter().any(|node| {
+                let name = &node.span.name;
+                (name.starts_with("LocalOutput")
+                    || name.starts_with("RemoteOutput")
+                    || name.starts_with("dispatch"))
+                    && node.is_slower_than(self.summary.slow_threshold.threshold_ns)
+            })
+        });
+        if output_blocked {
+            ActorState::OutputBlocked
+        } else {
+            ActorState::Running
+        }
+    }
+}
+
+impl Display for DotExport<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let graph =
+            ActorGraph::with_slow_threshold(self.trees, self.summary.slow_threshold.threshold_ns);
+
+        let mut jobs: BTreeMap<&str, BTreeMap<Fingerprint<'_>, Vec<u32>>> = BTreeMap::new();
+        for (actor_id, tree) in self.trees {
+            jobs.entry(tree.job_name().unwrap_or("unknown"))
+                .or_default()
+                .entry(tree.executor_fingerprint())
+                .or_default()
+                .push(*actor_id);
+        }
+
+        writeln!(f, "digraph actors {{")?;
+        writeln!(f, "  rankdir=LR;")?;
+        writeln!(
+            f,
+            "  node [shape=box, style=filled, fontname=\"monospace\"];"
+        )?;
+        for (job_index, (job_name, fragments)) in jobs.iter().enumerate() {
+            writeln!(f, "  subgraph cluster_{} {{", job_index)?;
+            writeln!(f, "    label=\"{}\";", escape(job_name))?;
+            for (fragment_index, (fingerprint, actor_ids)) in fragments.iter().enumerate() {
+                writeln!(
+                    f,
+                    "    subgraph cluster_{}_{} {{",
+                    job_index, fragment_index
+                )?;
+                writeln!(
+                    f,
+                    "      label=\"{}\";",
+                    escape(fingerprint.iter().map(|(kind, _)| kind).unique().join(", "))
+                )?;
+                writeln!(f, "      style=dashed;")?;
+                for actor_id in actor_ids {
+                    let state = self.actor_state(&graph, *actor_id);
+                    writeln!(
+                        f,
+                        "      actor_{} [label=\"Actor {}\", fillcolor=\"{}\"];",
+                        actor_id,
+                        actor_id,
+                        state.color()
+                    )?;
+                }
+                writeln!(f, "    }}")?;
+            }
+            writeln!(f, "  }}")?;
+        }
+        for edge in graph.edges() {
+            writeln!(
+                f,
+                "  actor_{} -> actor_{} [label=\"{:.3}s\"{}];",
+                edge.upstream,
+                edge.downstream,
+                edge.elapsed_ns as f64 / 1_000_000_000.0,
+                if edge.blocked {
+                    ", color=\"#d93025\", penwidth=2"
+                } else {
+                    ""
+                }
+            )?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    use crate::await_tree::{ActorGraph, ActorState, AnalyzeSummary, TreeView};
+
+    #[test]
+    fn test_dot_export() -> Result<()> {
+        let downstream = r#"Actor 3: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 60.000s]
+    Materialize 300000002 [!!! 60.000s]
+      Merge 300000001 [!!! 60.000s]
+        LocalInput (actor 2) [!!! 60.000s]
+"#;
+        let bottleneck = r#"Actor 2: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 60.000s]
+    HashAgg 200000003 [!!! 60.000s]
+      Merge 200000001 [0.001s]
+        LocalInput (actor 1) [0.001s]
+"#;
+        let upstream = r#"Actor 1: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 60.000s]
+    Source 100000001 [!!! 60.000s]
+      dispatch_chunk [!!! 60.000s]
+"#;
+        let trees = BTreeMap::from([
+            (1, TreeView::from_str(upstream).unwrap()),
+            (2, TreeView::from_str(bottleneck).unwrap()),
+            (3, TreeView::from_str(downstream).unwrap()),
+        ]);
+        let summary = AnalyzeSummary::from_trees(&trees);
+        let export = summary.dot(&trees);
+        let graph = ActorGraph::new(&trees);
+
+        assert_eq!(export.actor_state(&graph, 1), ActorState::OutputBlocked);
+        assert_eq!(export.actor_state(&graph, 2), ActorState::Bottleneck);
+        assert_eq!(export.actor_state(&graph, 3), ActorState::InputBlocked);
+
+        let dot = export.to_string();
+        assert!(dot.starts_with("digraph actors {"));
+        assert!(dot.contains("actor_2 [label=\"Actor 2\", fillcolor=\"#f28b82\"];"));
+        assert!(
+            dot.contains("actor_2 -> actor_3 [label=\"60.000s\", color=\"#d93025\", penwidth=2];")
+        );
+        assert!(dot.contains("actor_1 -> actor_2 [label=\"0.001s\"];"));
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{Display, Formatter};
+
+use serde::Serialize;
+
+use crate::await_tree::graph::ActorGraph;
+use crate::await_tree::knowledge::KnownIssue;
+use crate::await_tree::tree::TreeView;
+use crate::await_tree::utils::format_span_path;
+
+/// How badly the graph is affected by a finding, judged by how long the triggering span has
+/// been pending relative to the slow span threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// With the default threshold of 10s, the buckets start at 30s, 120s and 600s.
+    fn from_elapsed_ns(elapsed_ns: u128, slow_ns: u128) -> Self {
+        match elapsed_ns / slow_ns.max(1) {
+            0..3 => Severity::Low,
+            3..12 => Severity::Medium,
+            12..60 => Severity::High,
+            _ => Severity::Critical,
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Severity::Low => "Low",
+            Severity::Medium => "Medium",
+            Severity::High => "High",
+            Severity::Critical => "Critical",
+        };
+        f.write_str(s)
+    }
+}
+
+/// The rule that produced a finding.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    /// A slow span whose children are comparatively fast.
+    FastChildren,
+    /// A slow storage operation, e.g. `store_get` or `fetch_block`.
+    IoBound,
+    /// A join executor keeping its downstream actors busy while their epochs are stuck.
+    JoinAmplification,
+    /// An executor much slower than the same executor of the sibling actors in the fragment,
+    /// usually due to data skew or a hot key.
+    Straggler,
+}
+
+impl Display for FindingKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            FindingKind::FastChildren => "fast children",
+            FindingKind::IoBound => "IO bound",
+            FindingKind::JoinAmplification => "join amplification",
+            FindingKind::Straggler => "straggler",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Statistics of the children of the span that triggered a rule.
+#[derive(Debug, Clone, Default)]
+pub struct ChildStats {
+    pub count: usize,
+    /// Average elapsed time of the children in nanoseconds.
+    pub avg_ns: u128,
+    /// Maximum elapsed time of the children in nanoseconds.
+    pub max_ns: u128,
+}
+
+impl Display for ChildStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.count == 0 {
+            return f.write_str("none");
+        }
+        write!(
+            f,
+            "count {}, avg {:.3}s, max {:.3}s",
+            self.count,
+            self.avg_ns as f64 / 1_000_000_000.0,
+            self.max_ns as f64 / 1_000_000_000.0
+        )
+    }
+}
+
+/// Where in the tree a rule was triggered.
+#[derive(Debug, Clone)]
+pub struct Evidence {
+    /// Span names from the root span down to the triggering span.
+    pub path: Vec<String>,
+    /// Child indices from the root span down to the triggering span, which can be passed to
+    /// [`TreeRender::bottleneck`](crate::await_tree::TreeRender::bottleneck).
+    pub path_indices: Vec<usize>,
+    pub children: ChildStats,
+}
+
+impl Display for Evidence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format_span_path(&self.path))
+    }
+}
+
+/// A bottleneck candidate found in one actor, ranked against the others by [`Finding::score`].
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub actor_id: u32,
+    pub kind: FindingKind,
+    /// Name of the span that triggered the rule.
+    pub span: String,
+    /// Elapsed time of the triggering span in nanoseconds.
+    pub elapsed_ns: u128,
+    /// Elapsed time of the triggering span divided by the average elapsed time of its
+    /// children, if the rule compares them.
+    pub children_ratio: Option<f64>,
+    /// Median elapsed time of the same executor in the sibling actors of the fragment, if the
+    /// rule compares them.
+    pub sibling_median_ns: Option<u128>,
+    pub evidence: Evidence,
+    /// Number of actors of the same job sharing the pattern, including this one.
+    pub sibling_count: usize,
+    /// Number of actors of the same job.
+    pub job_actor_count: usize,
+    /// Number of actors blocked on the input from this actor. `None` if the dump contains no
+    /// exchange input spans, so that the graph position is unknown.
+    pub downstream_waiters: Option<usize>,
+    pub severity: Severity,
+    /// How likely the finding is the root cause, in `[0, 1]`.
+    pub confidence: f64,
+    /// Ranking score in `[0, 100]`, combining the elapsed time and the confidence.
+    pub score: f64,
+    /// Known issues matching the finding, see [`KnowledgeBase`](crate::await_tree::KnowledgeBase).
+    pub hints: Vec<KnownIssue>,
+}
+
+impl Finding {
+    /// Creates a finding triggered by the span at `path` of the tree, rated against the slow
+    /// span threshold `slow_ns`.
+    pub(crate) fn new(
+        actor_id: u32,
+        kind: FindingKind,
+        tree: &TreeView,
+        path: Vec<usize>,
+        slow_ns: u128,
+    ) -> Self {
+        let node = path
+            .iter()
+            .fold(&tree.tree, |node, index| &node.children[*index]);
+        let children = ChildStats {
+            count: node.children.len(),
+            avg_ns: node.children.iter().map(|c| c.elapsed_ns).sum::<u128>()
+                / node.children.len().max(1) as u128,
+            max_ns: node
+                .children
+                .iter()
+                .map(|c| c.elapsed_ns)
+                .max()
+                .unwrap_or(0),
+        };
+        let children_ratio = (kind == FindingKind::FastChildren)
+            .then(|| node.elapsed_ns as f64 / 1_000_000_000.0 / node.children_avg_secs());
+        let elapsed_ns = node.elapsed_ns;
+        Self {
+            actor_id,
+            kind,
+            span: node.span.name.clone(),
+            elapsed_ns,
+            children_ratio,
+            sibling_median_ns: None,
+            evidence: Evidence {
+                path: tree.span_names(&path),
+                path_indices: path,
+                children,
+            },
+            sibling_count: 1,
+            job_actor_count: 1,
+            downstream_waiters: None,
+            severity: Severity::from_elapsed_ns(elapsed_ns, slow_ns),
+            confidence: 0.0,
+            score: 0.0,
+            hints: Vec::new(),
+        }
+    }
+
+    /// Computes the confidence and the score from the collected signals.
+    ///
+    /// - A large parent/children ratio means the span itself is slow rather than waiting.
+    ///   Likewise, a large ratio to the siblings' median means the actor itself is slow.
+    /// - A pattern shared by most sibling actors is systemic rather than noise.
+    /// - An actor that others are blocked on sits at the front of the stuck part of the graph.
+    fn rank(&mut self, slow_ns: u128) {
+        let ratio_factor = match (self.children_ratio, self.sibling_median_ns) {
+            (Some(ratio), _) => (1.0 - 5.0 / ratio).clamp(0.0, 1.0),
+            (None, Some(median_ns)) => {
+                let ratio = self.elapsed_ns as f64 / median_ns.max(1) as f64;
+                (1.0 - 2.0 / ratio).clamp(0.0, 1.0)
+            }
+            (None, None) => 0.5,
+        };
+        let sibling_factor = self.sibling_count as f64 / self.job_actor_count.max(1) as f64;
+        let mut factors = vec![(ratio_factor, 0.5), (sibling_factor, 0.25)];
+        if let Some(waiters) = self.downstream_waiters {
+            factors.push((if waiters > 0 { 1.0 } else { 0.0 }, 0.25));
+        }
+        let weight_sum: f64 = factors.iter().map(|(_, weight)| weight).sum();
+        self.confidence = factors
+            .iter()
+            .map(|(factor, weight)| factor * weight)
+            .sum::<f64>()
+            / weight_sum;
+
+        // The threshold -> 0, 10x -> 0.5, 100x and beyond -> 1
+        let elapsed_ratio = self.elapsed_ns as f64 / slow_ns.max(1) as f64;
+        let elapsed_factor = (elapsed_ratio.log10() / 2.0).clamp(0.0, 1.0);
+        self.score = 100.0 * (0.3 * elapsed_factor + 0.7 * self.confidence);
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] score {:.1}, confidence {:.2}: {} at `{}` [{:.3}s] in actor {}",
+            self.severity,
+            self.score,
+            self.confidence,
+            self.kind,
+            self.span,
+            self.elapsed_ns as f64 / 1_000_000_000.0,
+            self.actor_id
+        )?;
+        write!(
+            f,
+            " ({}/{} actors of the job",
+            self.sibling_count, self.job_actor_count
+        )?;
+        if let Some(median_ns) = self.sibling_median_ns {
+            write!(
+                f,
+                ", {:.1}x the sibling median",
+                self.elapsed_ns as f64 / median_ns.max(1) as f64
+            )?;
+        }
+        if let Some(waiters) = self.downstream_waiters {
+            write!(f, ", {} downstream waiters", waiters)?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// Fills in the cross-actor signals of the findings, computes their scores against the slow
+/// span threshold `slow_ns` and sorts them from the most to the least likely root cause.
+pub(crate) fn rank_findings(
+    findings: &mut [Finding],
+    trees: &BTreeMap<u32, TreeView>,
+    graph: &ActorGraph,
+    slow_ns: u128,
+) {
+    let mut job_actor_count: HashMap<&str, usize> = HashMap::new();
+    for tree in trees.values() {
+        *job_actor_count
+            .entry(tree.job_name().unwrap_or(""))
+            .or_default() += 1;
+    }
+
+    let mut pattern_actors: HashMap<(&str, FindingKind), BTreeSet<u32>> = HashMap::new();
+    for finding in findings.iter() {
+        let job_name = trees
+            .get(&finding.actor_id)
+            .and_then(|tree| tree.job_name())
+            .unwrap_or("");
+        pattern_actors
+            .entry((job_name, finding.kind.clone()))
+            .or_default()
+            .insert(finding.actor_id);
+    }
+
+    // actor id -> actors blocked on its output
+    let mut waiters: HashMap<u32, BTreeSet<u32>> = HashMap::new();
+    for edge in graph.edges().filter(|edge| edge.blocked) {
+        waiters
+            .entry(edge.upstream)
+            .or_default()
+            .insert(edge.downstream);
+    }
+
+    for finding in findings.iter_mut() {
+        let job_name = trees
+            .get(&finding.actor_id)
+            .and_then(|tree| tree.job_name())
+            .unwrap_or("");
+        finding.job_actor_count = job_actor_count.get(job_name).copied().unwrap_or(1);
+        finding.sibling_count = pattern_actors
+            .get(&(job_name, finding.kind.clone()))
+            .map_or(1, |actors| actors.len());
+        if !graph.is_empty() {
+            finding.downstream_waiters =
+                Some(waiters.get(&finding.actor_id).map_or(0, |w| w.len()));
+        }
+        finding.rank(slow_ns);
+    }
+    sort_findings(findings);
+}
+
+/// Sorts the findings by descending score. Ties are broken by actor id and kind so that the
+/// order does not depend on how the findings were collected.
+pub(crate) fn sort_findings(findings: &mut [Finding]) {
+    findings.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.actor_id.cmp(&b.actor_id))
+            .then(a.kind.cmp(&b.kind))
+    });
+}
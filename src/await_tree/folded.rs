@@ -0,0 +1,154 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Folded-stack export (`a;b;c weight`) for flamegraph tooling such as inferno.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use crate::await_tree::tree::{SpanNodeView, TreeView};
+use crate::await_tree::utils::{normalize_span_name, parse_traces};
+
+/// What a stack is weighted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StackWeight {
+    /// Elapsed time of the leaf span in microseconds.
+    #[default]
+    Elapsed,
+    /// Number of actors with the stack.
+    ActorCount,
+}
+
+/// Root-to-leaf span paths of one or more dumps, with normalized span names so that executor
+/// ids and epochs do not split identical stacks.
+#[derive(Debug, Clone, Default)]
+pub struct FoldedStacks {
+    weight: StackWeight,
+    stacks: BTreeMap<Vec<String>, u128>,
+}
+
+impl FoldedStacks {
+    pub fn new(weight: StackWeight) -> Self {
+        Self {
+            weight,
+            stacks: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the stacks of the trees of one dump.
+    pub fn add_trees(&mut self, trees: &BTreeMap<u32, TreeView>) {
+        for tree in trees.values() {
+            // The job name tells the jobs apart at the root frame instead of a bare `Actor`.
+            let root_frame = match tree.job_name() {
+                Some(job_name) => job_name.replace(';', ","),
+                None => normalize_span_name(&tree.tree.span.name),
+            };
+            let mut actor_stacks = BTreeMap::new();
+            if tree.tree.children.is_empty() {
+                actor_stacks.insert(vec![root_frame.clone()], tree.tree.elapsed_ns);
+            }
+            for node in tree.tree.children.iter().chain(&tree.detached) {
+                collect_stacks(node, &mut vec![root_frame.clone()], &mut actor_stacks);
+            }
+            for (stack, elapsed_ns) in actor_stacks {
+                *self.stacks.entry(stack).or_default() += match self.weight {
+                    StackWeight::Elapsed => elapsed_ns / 1_000,
+                    StackWeight::ActorCount => 1,
+                };
+            }
+        }
+    }
+
+    /// Adds the stacks of the actor traces of one dump, as returned by
+    /// [`extract_actor_traces`](crate::await_tree::extract_actor_traces).
+    pub fn add_traces<'a, M>(&mut self, actor_traces: M) -> anyhow::Result<()>
+    where
+        M: IntoIterator<Item = (&'a u32, &'a String)>,
+    {
+        self.add_trees(&parse_traces(actor_traces)?);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+}
+
+/// Collects the root-to-leaf stacks under `node` with the summed elapsed time of their leaves.
+fn collect_stacks(
+    node: &SpanNodeView,
+    stack: &mut Vec<String>,
+    stacks: &mut BTreeMap<Vec<String>, u128>,
+) {
+    // `;` separates the frames of a stack.
+    stack.push(normalize_span_name(&node.span.name).replace(';', ","));
+    if node.children.is_empty() {
+        *stacks.entry(stack.clone()).or_default() += node.elapsed_ns;
+    }
+    for child in &node.children {
+        collect_stacks(child, stack, stacks);
+    }
+    stack.pop();
+}
+
+impl Display for FoldedStacks {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (stack, weight) in &self.stacks {
+            writeln!(f, "{} {}", stack.join(";"), weight)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    use crate::await_tree::{FoldedStacks, StackWeight, TreeView};
+
+    #[test]
+    fn test_folded_stacks() -> Result<()> {
+        let actor = |actor_id: u32, elapsed: &str| {
+            TreeView::from_str(&format!(
+                r#"Actor {actor_id}: `mv` [100.000s]
+  Epoch 8251479171792896 [{elapsed}]
+    HashAgg {actor_id}00000005 [{elapsed}]
+      store_get [{elapsed}]
+      Merge {actor_id}00000004 [0.001s]
+        LocalInput (actor 9) [0.001s]
+"#
+            ))
+            .unwrap()
+        };
+        let trees = BTreeMap::from([(1, actor(1, "1.000s")), (2, actor(2, "2.000s"))]);
+
+        let mut elapsed = FoldedStacks::new(StackWeight::Elapsed);
+        elapsed.add_trees(&trees);
+        assert_eq!(
+            elapsed.to_string(),
+            "mv;Epoch;HashAgg;Merge;LocalInput 2000\nmv;Epoch;HashAgg;store_get 3000000\n"
+        );
+
+        let mut actor_count = FoldedStacks::new(StackWeight::ActorCount);
+        actor_count.add_trees(&trees);
+        actor_count.add_trees(&BTreeMap::from([(3, actor(3, "3.000s"))]));
+        assert_eq!(
+            actor_count.to_string(),
+            "mv;Epoch;HashAgg;Merge;LocalInput 3\nmv;Epoch;HashAgg;store_get 3\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Graph between actors, linked through their exchange input spans like
+//! `LocalInput (actor 122807)`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+
+use crate::await_tree::tree::{TreeView, SLOW_SPAN_NS};
+use crate::await_tree::utils::{format_span_path, upstream_actor_id};
+
+/// An exchange input from an upstream actor.
+#[derive(Debug, Clone)]
+pub struct InputEdge {
+    pub downstream: u32,
+    pub upstream: u32,
+    /// Span names from the root span down to the exchange input span.
+    pub path: Vec<String>,
+    /// Elapsed time of the exchange input span in nanoseconds.
+    pub elapsed_ns: u128,
+    /// Whether the downstream actor is blocked on this input, i.e. the span is slow.
+    pub blocked: bool,
+}
+
+impl Display for InputEdge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Actor {} -> Actor {} [{:.3}s]: {}",
+            self.downstream,
+            self.upstream,
+            self.elapsed_ns as f64 / 1_000_000_000.0,
+            format_span_path(&self.path)
+        )
+    }
+}
+
+/// Graph of the exchange inputs between the actors of a dump.
+#[derive(Debug, Clone, Default)]
+pub struct ActorGraph {
+    /// Downstream actor id -> its inputs
+    inputs: BTreeMap<u32, Vec<InputEdge>>,
+}
+
+impl ActorGraph {
+    pub fn new(trees: &BTreeMap<u32, TreeView>) -> Self {
+        Self::with_slow_threshold(trees, SLOW_SPAN_NS)
+    }
+
+    /// Builds the graph with the inputs pending for at least `slow_ns` as blocked, e.g. with
+    /// [`AnalyzeSummary::slow_threshold_ns`](crate::await_tree::AnalyzeSummary::slow_threshold_ns).
+    pub fn with_slow_threshold(trees: &BTreeMap<u32, TreeView>, slow_ns: u128) -> Self {
+        let mut inputs: BTreeMap<u32, Vec<InputEdge>> = BTreeMap::new();
+        for (actor_id, tree) in trees {
+            for (path, node) in tree.tree.iter_with_path() {
+                if let Some(upstream) = upstream_actor_id(&node.span.name) {
+                    inputs.entry(*actor_id).or_default().push(InputEdge {
+                        downstream: *actor_id,
+                        upstream,
+                        path: tree.span_names(&path),
+                        elapsed_ns: node.elapsed_ns,
+                        blocked: node.is_slower_than(slow_ns),
+                    });
+                }
+            }
+        }
+        Self { inputs }
+    }
+
+    /// Whether the dump contains any exchange input span.
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Returns the inputs of the actor.
+    pub fn inputs(&self, actor_id: u32) -> &[InputEdge] {
+        self.inputs
+            .get(&actor_id)
+            .map_or(&[], |edges| edges.as_slice())
+    }
+
+    /// Returns the inputs the actor is blocked on.
+    pub fn blocked_inputs(&self, actor_id: u32) -> impl Iterator<Item = &InputEdge> {
+        self.inputs(actor_id).iter().filter(|edge| edge.blocked)
+    }
+
+    /// Iterates over all inputs of all actors.
+    pub fn edges(&self) -> impl Iterator<Item = &InputEdge> {
+        self.inputs.values().flatten()
+    }
+
+    /// Finds circular waits, in which every actor is blocked on the input from the next one.
+    ///
+    /// One shortest cycle is reported for each group of actors waiting on each other, i.e.
+    /// each strongly connected component of the blocked inputs, starting from its smallest
+    /// actor id.
+    pub fn find_cycles(&self) -> Vec<Vec<&InputEdge>> {
+        let mut tarjan = Tarjan::default();
+        for actor_id in self.inputs.keys() {
+            if !tarjan.index.contains_key(actor_id) {
+                tarjan.connect(self, *actor_id);
+            }
+        }
+
+        let mut cycles = vec![];
+        for component in tarjan.components {
+            let start = *component.iter().min().unwrap();
+            // breadth-first search for the shortest way back to `start`
+            let mut via: BTreeMap<u32, &InputEdge> = BTreeMap::new();
+            let mut queue = VecDeque::from([start]);
+            'search: while let Some(actor_id) = queue.pop_front() {
+                for edge in self.blocked_inputs(actor_id) {
+                    if !component.contains(&edge.upstream) || via.contains_key(&edge.upstream) {
+                        continue;
+                    }
+                    via.insert(edge.upstream, edge);
+                    if edge.upstream == start {
+                        break 'search;
+                    }
+                    queue.push_back(edge.upstream);
+                }
+            }
+            let Some(mut edge) = via.get(&start).copied() else {
+                continue;
+            };
+            let mut cycle = vec![edge];
+            while edge.downstream != start {
+                edge = via[&edge.downstream];
+                cycle.push(edge);
+            }
+            cycle.reverse();
+            cycles.push(cycle);
+        }
+        cycles.sort_by_key(|cycle| cycle[0].downstream);
+        cycles
+    }
+}
+
+/// State of Tarjan's strongly connected components algorithm over the blocked inputs.
+#[derive(Default)]
+struct Tarjan {
+    index: HashMap<u32, usize>,
+    low_link: HashMap<u32, usize>,
+    stack: Vec<u32>,
+    on_stack: HashSet<u32>,
+    /// Components that contain a cycle, i.e. more than one actor or a self-loop.
+    components: Vec<BTreeSet<u32>>,
+}
+
+impl Tarjan {
+    fn connect(&mut self, graph: &ActorGraph, actor_id: u32) {
+        let index = self.index.len();
+        self.index.insert(actor_id, index);
+        self.low_link.insert(actor_id, index);
+        self.stack.push(actor_id);
+        self.on_stack.insert(actor_id);
+
+        let mut self_loop = false;
+        for edge in graph.blocked_inputs(actor_id) {
+            self_loop |= edge.upstream == actor_id;
+            if !self.index.contains_key(&edge.upstream) {
+                self.connect(graph, edge.upstream);
+                let low_link = self.low_link[&actor_id].min(self.low_link[&edge.upstream]);
+                self.low_link.insert(actor_id, low_link);
+            } else if self.on_stack.contains(&edge.upstream) {
+                let low_link = self.low_link[&actor_id].min(self.index[&edge.upstream]);
+                self.low_link.insert(actor_id, low_link);
+            }
+        }
+
+        if self.low_link[&actor_id] == index {
+            let mut component = BTreeSet::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(&member);
+                component.insert(member);
+                if member == actor_id {
+                    break;
+                }
+            }
+            if component.len() > 1 || self_loop {
+                self.components.push(component);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    use crate::await_tree::{ActorGraph, TreeView};
+
+    #[test]
+    fn test_find_cycles() -> Result<()> {
+        let actor = |actor_id: u32, upstream: u32| {
+            TreeView::from_str(&format!(
+                r#"Actor {actor_id}: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 60.000s]
+    Merge {actor_id}00000001 [!!! 60.000s]
+      LocalInput (actor {upstream}) [!!! 60.000s]
+"#
+            ))
+            .unwrap()
+        };
+        let trees = BTreeMap::from([
+            (1, actor(1, 2)),
+            (2, actor(2, 3)),
+            (3, actor(3, 1)),
+            (4, actor(4, 1)),
+        ]);
+        let graph = ActorGraph::new(&trees);
+
+        let cycles = graph.find_cycles();
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(
+            cycles[0]
+                .iter()
+                .map(|edge| (edge.downstream, edge.upstream))
+                .collect::<Vec<_>>(),
+            [(1, 2), (2, 3), (3, 1)]
+        );
+        assert_eq!(
+            cycles[0][0].path,
+            [
+                "Actor 1: `mv`",
+                "Epoch 8251479171792896",
+                "Merge 100000001",
+                "LocalInput (actor 2)"
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use itertools::Itertools;
+
+use crate::await_tree::analyze::{AnalyzeSummary, RANKED_FINDINGS_LIMIT};
+use crate::await_tree::tree::{SpanNodeView, TreeView};
+
+/// Width in pixels of the elapsed time bar of a span as slow as the root span.
+const BAR_WIDTH_PX: u128 = 200;
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }
+th { background: #f3f3f3; }
+td.num { text-align: right; }
+.tree { font-family: monospace; font-size: 13px; }
+.tree details, .tree .leaf { margin-left: 1.5em; }
+.tree > details { margin-left: 0; }
+.node { display: inline-flex; align-items: center; gap: 8px; }
+.bar { display: inline-block; height: 8px; background: #8ab4f8; }
+.slow > .name, .slow > .elapsed { color: #c5221f; }
+.long-running > .name { color: #888; }
+.bottleneck { background: #fde293; }
+.current::after { content: "<== current"; color: #888; }
+.hidden { display: none; }
+"#;
+
+const SCRIPT: &str = r#"
+function filterActors(text) {
+  text = text.toLowerCase();
+  for (const row of document.querySelectorAll('#actors tbody tr')) {
+    row.classList.toggle('hidden', !row.textContent.toLowerCase().includes(text));
+  }
+}
+function expandAll(open) {
+  for (const details of document.querySelectorAll('.tree details')) {
+    details.open = open;
+  }
+}
+"#;
+
+/// Self-contained HTML report of an [`AnalyzeSummary`] and the trees it was built from, with
+/// inline CSS and JS so that it can be attached to tickets.
+///
+/// Created by [`AnalyzeSummary::html`]. Span trees are collapsible, with elapsed time bars and
+/// the bottleneck spans of the findings highlighted.
+pub struct HtmlReport<'a> {
+    summary: &'a AnalyzeSummary,
+    trees: &'a BTreeMap<u32, TreeView>,
+}
+
+impl AnalyzeSummary {
+    /// Renders the summary together with the trees it was built from as HTML.
+    pub fn html<'a>(&'a self, trees: &'a BTreeMap<u32, TreeView>) -> HtmlReport<'a> {
+        HtmlReport {
+            summary: self,
+            trees,
+        }
+    }
+}
+
+fn escape(text: impl Display) -> String {
+    let mut escaped = String::new();
+    for c in text.to_string().chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn secs(elapsed_ns: u128) -> String {
+    format!("{:.3}s", elapsed_ns as f64 / 1_000_000_000.0)
+}
+
+/// What the nodes of one tree are rendered against.
+struct TreeContext<'a> {
+    tree: &'a TreeView,
+    /// Elapsed time of a full-width bar.
+    max_ns: u128,
+    /// Threshold of slow spans in nanoseconds.
+    slow_ns: u128,
+    /// Paths of the bottleneck spans in the main tree.
+    bottlenecks: BTreeSet<&'a [usize]>,
+}
+
+impl TreeContext<'_> {
+    fn fmt_node(
+        &self,
+        f: &mut Formatter<'_>,
+        node: &SpanNodeView,
+        path: &mut Vec<usize>,
+        attached: bool,
+    ) -> std::fmt::Result {
+        let (tree, max_ns) = (self.tree, self.max_ns);
+        let mut classes = vec!["node"];
+        if node.is_slower_than(self.slow_ns) {
+            classes.push("slow");
+        }
+        if node.span.is_long_running {
+            classes.push("long-running");
+        }
+        if attached && self.bottlenecks.contains(path.as_slice()) {
+            classes.push("bottleneck");
+        }
+        if !path.is_empty() && node.id == tree.current {
+            classes.push("current");
+        }
+        // Detached spans may have been pending for longer than the root span.
+        let bar_width = (node.elapsed_ns * BAR_WIDTH_PX / max_ns.max(1)).min(BAR_WIDTH_PX);
+        let label = format!(
+            r#"<span class="{}"><span class="name">{}</span><span class="elapsed">{}</span><span class="bar" style="width: {}px"></span></span>"#,
+            classes.join(" "),
+            escape(&node.span.name),
+            secs(node.elapsed_ns),
+            bar_width
+        );
+
+        if node.children.is_empty() {
+            return writeln!(f, r#"<div class="leaf">{}</div>"#, label);
+        }
+        writeln!(f, "<details open><summary>{}</summary>", label)?;
+        for (index, child) in node
+            .children
+            .iter()
+            .enumerate()
+            .sorted_by_key(|(index, n)| (n.elapsed_ns, *index))
+        {
+            path.push(index);
+            self.fmt_node(f, child, path, attached)?;
+            path.pop();
+        }
+        writeln!(f, "</details>")
+    }
+}
+
+impl HtmlReport<'_> {
+    fn fmt_tree(&self, f: &mut Formatter<'_>, actor_id: u32, tree: &TreeView) -> std::fmt::Result {
+        let context = TreeContext {
+            tree,
+            max_ns: tree.tree.elapsed_ns,
+            slow_ns: self.summary.slow_threshold.threshold_ns,
+            bottlenecks: self
+                .summary
+                .findings
+                .iter()
+                .filter(|finding| finding.actor_id == actor_id)
+                .map(|finding| finding.evidence.path_indices.as_slice())
+                .collect(),
+        };
+        writeln!(f, r#"<div class="tree" id="actor-{}">"#, actor_id)?;
+        context.fmt_node(f, &tree.tree, &mut vec![], true)?;
+        for node in &tree.detached {
+            writeln!(f, "<p>{}</p>", escape(tree.detached_header(node)))?;
+            context.fmt_node(f, node, &mut vec![], false)?;
+        }
+        writeln!(f, "</div>")
+    }
+
+    fn fmt_findings(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let findings = &self.summary.findings;
+        if !self.summary.bottleneck_found() {
+            return writeln!(f, "<p>No bottleneck actors detected.</p>");
+        }
+        if findings.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "<h2>Ranked Findings</h2>")?;
+        writeln!(f, "<table><thead><tr><th>#</th><th>Severity</th><th>Score</th><th>Kind</th><th>Span</th><th>Elapsed</th><th>Actor</th><th>Hints</th></tr></thead><tbody>")?;
+        for (rank, finding) in findings.iter().take(RANKED_FINDINGS_LIMIT).enumerate() {
+            writeln!(
+                f,
+                r##"<tr><td class="num">{}</td><td>{}</td><td class="num">{:.1}</td><td>{}</td><td><code>{}</code></td><td class="num">{}</td><td><a href="#actor-{}">{}</a></td><td>{}</td></tr>"##,
+                rank + 1,
+                finding.severity,
+                finding.score,
+                finding.kind,
+                escape(&finding.span),
+                secs(finding.elapsed_ns),
+                finding.actor_id,
+                finding.actor_id,
+                finding
+                    .hints
+                    .iter()
+                    .map(|hint| format!(
+                        r#"<span title="{}">{}</span>"#,
+                        escape(&hint.remediation),
+                        escape(&hint.title)
+                    ))
+                    .join("; ")
+            )?;
+        }
+        writeln!(f, "</tbody></table>")
+    }
+
+    fn fmt_wait_cycles(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.summary.wait_cycles.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "<h2>Wait-for Cycles</h2>")?;
+        writeln!(f, "<ul>")?;
+        for cycle in &self.summary.wait_cycles {
+            writeln!(
+                f,
+                "<li>{}</li>",
+                cycle
+                    .iter()
+                    .chain(cycle.first())
+                    .map(|edge| format!(
+                        r##"<a href="#actor-{}">Actor {}</a>"##,
+                        edge.downstream, edge.downstream
+                    ))
+                    .join(" → ")
+            )?;
+        }
+        writeln!(f, "</ul>")
+    }
+
+    fn fmt_actors(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "<h2>Actors</h2>")?;
+        writeln!(
+            f,
+            r#"<input type="search" placeholder="Filter actors" oninput="filterActors(this.value)">"#
+        )?;
+        writeln!(
+            f,
+            r#"<table id="actors"><thead><tr><th>Actor</th><th>Job</th><th>Uptime</th><th>Epoch age</th><th>Current await</th><th>Findings</th></tr></thead><tbody>"#
+        )?;
+        for (actor_id, tree) in self.trees {
+            let findings = self
+                .summary
+                .findings
+                .iter()
+                .filter(|finding| finding.actor_id == *actor_id)
+                .map(|finding| finding.kind.to_string())
+                .unique()
+                .join(", ");
+            writeln!(
+                f,
+                r##"<tr><td><a href="#actor-{}">{}</a></td><td>{}</td><td class="num">{}</td><td class="num">{}</td><td>{}</td><td>{}</td></tr>"##,
+                actor_id,
+                actor_id,
+                escape(tree.job_name().unwrap_or("unknown")),
+                secs(tree.tree.elapsed_ns),
+                tree.epoch_age_ns().map(secs).unwrap_or_default(),
+                tree.current_await()
+                    .map(|current| escape(current.kind))
+                    .unwrap_or_default(),
+                findings
+            )?;
+        }
+        writeln!(f, "</tbody></table>")
+    }
+}
+
+impl Display for HtmlReport<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "<!DOCTYPE html>")?;
+        writeln!(f, r#"<html><head><meta charset="utf-8">"#)?;
+        writeln!(f, "<title>Await-Tree Analyze Summary</title>")?;
+        writeln!(f, "<style>{}</style>", STYLE)?;
+        writeln!(f, "<script>{}</script>", SCRIPT)?;
+        writeln!(f, "</head><body>")?;
+        writeln!(f, "<h1>Analyze Summary</h1>")?;
+        writeln!(
+            f,
+            "<p>Total actors analyzed: {}<br>Slow span threshold: {}</p>",
+            self.summary.total_actors_analyzed,
+            escape(&self.summary.slow_threshold)
+        )?;
+
+        self.fmt_findings(f)?;
+        self.fmt_wait_cycles(f)?;
+        self.fmt_actors(f)?;
+
+        writeln!(f, "<h2>Span Trees</h2>")?;
+        writeln!(
+            f,
+            r#"<button onclick="expandAll(true)">Expand all</button> <button onclick="expandAll(false)">Collapse all</button>"#
+        )?;
+        for (actor_id, tree) in self.trees {
+            writeln!(f, "<h3>Actor {}</h3>", actor_id)?;
+            self.fmt_tree(f, *actor_id, tree)?;
+        }
+        writeln!(f, "</body></html>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    use crate::await_tree::{AnalyzeSummary, TreeView};
+
+    #[test]
+    fn test_html_report() -> Result<()> {
+        let bottleneck = r#"Actor 2: `SELECT * FROM t WHERE a < b` [1000.000s]
+  Epoch 8251479171792896 [!!! 900.000s]
+    Materialize 200000007 [!!! 900.000s]
+      HashAgg 200000005 [!!! 900.000s]
+        Merge 200000004 [0.001s]
+"#;
+        let trees = BTreeMap::from([(2, TreeView::from_str(bottleneck).unwrap())]);
+        let summary = AnalyzeSummary::from_trees(&trees);
+        let html = summary.html(&trees).to_string();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("SELECT * FROM t WHERE a &lt; b"));
+        assert!(html.contains(
+            r#"<span class="node slow bottleneck"><span class="name">HashAgg 200000005</span>"#
+        ));
+        assert!(html.contains(r#"<div class="leaf"><span class="node">"#));
+
+        let detached = r#"Actor 3: `mv` [10.000s]
+  Epoch 8251479171792896 [1.000s]
+[Detached 4]
+  store_flush [!!! 40.000s]
+"#;
+        let trees = BTreeMap::from([(3, TreeView::from_str(detached).unwrap())]);
+        let html = AnalyzeSummary::from_trees(&trees).html(&trees).to_string();
+        assert!(html.contains(r#"<span class="bar" style="width: 20px">"#));
+        assert!(html.contains(r#"<span class="bar" style="width: 200px">"#));
+        assert!(!html.contains(r#"style="width: 800px""#));
+
+        // A wait-for cycle without findings is a bottleneck as well.
+        let waiting = |actor_id: u32, upstream: u32| {
+            TreeView::from_str(&format!(
+                r#"Actor {actor_id}: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 60.000s]
+    Merge {actor_id}00000001 [!!! 60.000s]
+      LocalInput (actor {upstream}) [!!! 60.000s]
+"#
+            ))
+            .unwrap()
+        };
+        let trees = BTreeMap::from([(1, waiting(1, 2)), (2, waiting(2, 1))]);
+        let summary = AnalyzeSummary::from_trees(&trees);
+        assert!(summary.findings().is_empty());
+        let html = summary.html(&trees).to_string();
+        assert!(!html.contains("No bottleneck actors detected."));
+        assert!(html.contains(
+            r##"<li><a href="#actor-1">Actor 1</a> → <a href="#actor-2">Actor 2</a> → <a href="#actor-1">Actor 1</a></li>"##
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::{Display, Formatter};
+
+use crate::await_tree::finding::{Finding, FindingKind};
+
+/// A known stall pattern with a human explanation and a suggested remediation.
+#[derive(Debug, Clone)]
+pub struct KnownIssue {
+    pub title: String,
+    /// Only findings of this kind match, or findings of any kind if `None`.
+    pub kind: Option<FindingKind>,
+    /// Findings whose triggering span name contains this pattern match.
+    pub span_pattern: String,
+    pub explanation: String,
+    pub remediation: String,
+}
+
+impl KnownIssue {
+    pub fn new(
+        title: impl Into<String>,
+        kind: Option<FindingKind>,
+        span_pattern: impl Into<String>,
+        explanation: impl Into<String>,
+        remediation: impl Into<String>,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            kind,
+            span_pattern: span_pattern.into(),
+            explanation: explanation.into(),
+            remediation: remediation.into(),
+        }
+    }
+
+    pub fn matches(&self, finding: &Finding) -> bool {
+        self.kind
+            .as_ref()
+            .map_or(true, |kind| *kind == finding.kind)
+            && finding.span.contains(&self.span_pattern)
+    }
+}
+
+impl Display for KnownIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.title, self.explanation)
+    }
+}
+
+/// Catalogue of known stall patterns, matched against the findings of an analysis.
+///
+/// [`KnowledgeBase::default`] contains the built-in patterns. More can be added with
+/// [`KnowledgeBase::add`], e.g. for issues specific to a deployment.
+#[derive(Debug, Clone)]
+pub struct KnowledgeBase {
+    issues: Vec<KnownIssue>,
+}
+
+impl KnowledgeBase {
+    /// Creates a knowledge base without any pattern.
+    pub fn empty() -> Self {
+        Self { issues: Vec::new() }
+    }
+
+    pub fn add(&mut self, issue: KnownIssue) {
+        self.issues.push(issue);
+    }
+
+    pub fn issues(&self) -> &[KnownIssue] {
+        &self.issues
+    }
+
+    /// Returns the known issues matching the finding, in the order they were added.
+    pub fn lookup<'a>(&'a self, finding: &'a Finding) -> impl Iterator<Item = &'a KnownIssue> {
+        self.issues.iter().filter(|issue| issue.matches(finding))
+    }
+}
+
+impl Default for KnowledgeBase {
+    fn default() -> Self {
+        let mut kb = Self::empty();
+        kb.add(KnownIssue::new(
+            "Object storage upload pressure",
+            Some(FindingKind::IoBound),
+            "store_flush",
+            "Flushing the state of a checkpoint is waiting for uploads to object storage.",
+            "Check the latency and throughput of the object storage, and the compactor \
+             backlog. Fewer checkpoints, e.g. a larger `checkpoint_frequency`, reduce the \
+             upload pressure.",
+        ));
+        kb.add(KnownIssue::new(
+            "Block cache misses",
+            Some(FindingKind::IoBound),
+            "fetch_block",
+            "State reads miss the block cache and fetch blocks from object storage.",
+            "Increase the block cache and meta cache of the compute nodes, or check the \
+             latency of the object storage.",
+        ));
+        kb.add(KnownIssue::new(
+            "Slow state store reads",
+            Some(FindingKind::IoBound),
+            "store_get",
+            "Point lookups of the operator state miss the operator cache and go to the state \
+             store.",
+            "Increase the memory of the compute nodes so that more state is cached, and check \
+             for cache misses in the block cache metrics.",
+        ));
+        kb.add(KnownIssue::new(
+            "Slow state store scans",
+            Some(FindingKind::IoBound),
+            "store_iter",
+            "Range scans of the operator state are slow, e.g. for a large join key or a \
+             temporal filter.",
+            "Check the number of rows per join key or per scanned range, and the block cache \
+             hit rate.",
+        ));
+        kb.add(KnownIssue::new(
+            "Join amplification",
+            Some(FindingKind::JoinAmplification),
+            "Join",
+            "Each input row of the join matches many rows of the other side, so that the \
+             join keeps its downstream busy while the barrier cannot pass.",
+            "Check the join keys for hot values or missing join conditions. Reducing the \
+             output of the join, e.g. by filtering or aggregating earlier, helps.",
+        ));
+        kb.add(KnownIssue::new(
+            "Slow hash join",
+            Some(FindingKind::FastChildren),
+            "HashJoin",
+            "The join is busy processing its input rather than waiting for it, usually due \
+             to a high join amplification or cache misses on the join state.",
+            "Check the join amplification and the cache miss rate of the join state. \
+             Increase the parallelism or the memory of the compute nodes.",
+        ));
+        kb.add(KnownIssue::new(
+            "Slow hash aggregation",
+            Some(FindingKind::FastChildren),
+            "HashAgg",
+            "The aggregation is busy processing its input rather than waiting for it, \
+             usually due to a high number of groups or cache misses on the aggregation state.",
+            "Increase the parallelism or the memory of the compute nodes. Check whether some \
+             group keys are much hotter than others.",
+        ));
+        kb.add(KnownIssue::new(
+            "Slow sink",
+            Some(FindingKind::FastChildren),
+            "Sink",
+            "The sink is waiting for the external system to accept its writes.",
+            "Check the availability and the write latency of the downstream system, and the \
+             sink's batching options.",
+        ));
+        kb
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    use crate::await_tree::{AnalyzeConfig, AnalyzeSummary, FindingKind, KnownIssue, TreeView};
+
+    #[test]
+    fn test_hints_from_knowledge_base() -> Result<()> {
+        let tree = r#"Actor 1: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 12.000s]
+    Materialize 100000007 [!!! 12.000s]
+      store_flush [!!! 12.000s]
+"#;
+        let trees = BTreeMap::from([(1, TreeView::from_str(tree).unwrap())]);
+        let mut config = AnalyzeConfig::default();
+        config.knowledge_base.add(KnownIssue::new(
+            "Custom",
+            None,
+            "store_",
+            "A custom explanation.",
+            "A custom remediation.",
+        ));
+        let summary = AnalyzeSummary::from_trees_with_config(&trees, &config);
+
+        let finding = &summary.findings()[0];
+        assert_eq!(finding.kind, FindingKind::IoBound);
+        assert_eq!(
+            finding
+                .hints
+                .iter()
+                .map(|hint| hint.title.as_str())
+                .collect::<Vec<_>>(),
+            ["Object storage upload pressure", "Custom"]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,338 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::{Display, Formatter};
+
+use itertools::Itertools;
+
+use crate::await_tree::analyze::{AnalyzeSummary, RANKED_FINDINGS_LIMIT, TOP_SPAN_KINDS_LIMIT};
+use crate::await_tree::stats::Distribution;
+
+/// Markdown rendering of an [`AnalyzeSummary`], for incident tickets and GitHub issues.
+///
+/// Created by [`AnalyzeSummary::markdown`]. Statistics are rendered as tables, and each
+/// bottleneck tree as a collapsible `<details>` block.
+pub struct MarkdownReport<'a> {
+    summary: &'a AnalyzeSummary,
+}
+
+impl AnalyzeSummary {
+    pub fn markdown(&self) -> MarkdownReport<'_> {
+        MarkdownReport { summary: self }
+    }
+}
+
+/// Escapes text for a table cell.
+fn cell(text: impl Display) -> String {
+    text.to_string().replace('|', "\\|").replace('\n', " ")
+}
+
+/// Formats text as inline code, with a double backtick fence if the text contains backticks,
+/// e.g. the job names quoted in root spans.
+fn code(text: impl Display) -> String {
+    let text = text.to_string();
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+fn secs(elapsed_ns: u128) -> String {
+    format!("{:.3}s", elapsed_ns as f64 / 1_000_000_000.0)
+}
+
+fn actor_ids<'a>(actor_ids: impl IntoIterator<Item = &'a u32>) -> String {
+    actor_ids.into_iter().join(", ")
+}
+
+impl MarkdownReport<'_> {
+    fn fmt_distributions(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let distributions = [
+            ("Actor uptime", self.summary.actor_elapsed()),
+            ("Epoch age", self.summary.epoch_elapsed()),
+        ];
+        if distributions.iter().all(|(_, d)| d.is_empty()) {
+            return Ok(());
+        }
+        writeln!(f, "\n## Elapsed Time Statistics\n")?;
+        writeln!(f, "| | Count | Min | P50 | P90 | P99 | Max |")?;
+        writeln!(f, "|---|---:|---:|---:|---:|---:|---:|")?;
+        for (name, distribution) in &distributions {
+            let Some(min) = distribution.min() else {
+                continue;
+            };
+            write!(f, "| {} | {} | {} ", name, distribution.len(), secs(min))?;
+            for p in [50.0, 90.0, 99.0] {
+                write!(f, "| {} ", secs(distribution.percentile(p).unwrap()))?;
+            }
+            writeln!(f, "| {} |", secs(distribution.max().unwrap()))?;
+        }
+        for (name, distribution) in &distributions {
+            self.fmt_outliers(f, name, distribution)?;
+        }
+        Ok(())
+    }
+
+    fn fmt_outliers(
+        &self,
+        f: &mut Formatter<'_>,
+        name: &str,
+        distribution: &Distribution,
+    ) -> std::fmt::Result {
+        let outliers = distribution.outliers();
+        if !outliers.is_empty() {
+            writeln!(
+                f,
+                "\n{} outliers: {}",
+                name,
+                outliers
+                    .iter()
+                    .map(|(elapsed_ns, actor_id)| format!(
+                        "actor {} ({})",
+                        actor_id,
+                        secs(*elapsed_ns)
+                    ))
+                    .join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for MarkdownReport<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let summary = self.summary;
+        writeln!(f, "# Analyze Summary\n")?;
+        writeln!(
+            f,
+            "- Total actors analyzed: {}",
+            summary.total_actors_analyzed
+        )?;
+        writeln!(f, "- Slow span threshold: {}", summary.slow_threshold)?;
+
+        self.fmt_distributions(f)?;
+
+        let top_span_kinds = summary.top_span_kinds(TOP_SPAN_KINDS_LIMIT);
+        if !top_span_kinds.is_empty() {
+            writeln!(f, "\n## Slowest Span Kinds\n")?;
+            writeln!(f, "| Span kind | Count | Total | Max | Actors |")?;
+            writeln!(f, "|---|---:|---:|---:|---:|")?;
+            for (kind, stats) in top_span_kinds {
+                writeln!(
+                    f,
+                    "| {} | {} | {} | {} | {} |",
+                    cell(code(kind)),
+                    stats.count,
+                    secs(stats.total_ns),
+                    secs(stats.max_ns),
+                    stats.actors.len()
+                )?;
+            }
+        }
+
+        let current_awaits = &summary.current_awaits;
+        if !current_awaits.actors.is_empty() {
+            writeln!(f, "\n## Current Await Points\n")?;
+            writeln!(f, "| Span kind | Actors |")?;
+            writeln!(f, "|---|---:|")?;
+            for (kind, actor_ids) in
+                current_awaits
+                    .actors_by_kind()
+                    .into_iter()
+                    .sorted_by(|(a_kind, a), (b_kind, b)| {
+                        b.len().cmp(&a.len()).then(a_kind.cmp(b_kind))
+                    })
+            {
+                writeln!(f, "| {} | {} |", cell(code(kind)), actor_ids.len())?;
+            }
+            let unexpected = current_awaits.unexpected_actors().collect_vec();
+            if !unexpected.is_empty() {
+                writeln!(f, "\nActors parked on unexpected leaf spans:\n")?;
+                for (actor_id, current) in unexpected {
+                    writeln!(
+                        f,
+                        "- Actor {}: {} ({})",
+                        actor_id,
+                        code(current.path.join(" > ")),
+                        secs(current.elapsed_ns)
+                    )?;
+                }
+            }
+        }
+
+        if !summary.wait_cycles.is_empty() {
+            writeln!(f, "\n## Wait-for Cycles\n")?;
+            for cycle in &summary.wait_cycles {
+                writeln!(
+                    f,
+                    "- {}",
+                    cycle
+                        .iter()
+                        .map(|edge| format!("Actor {}", edge.downstream))
+                        .chain(
+                            cycle
+                                .first()
+                                .map(|edge| format!("Actor {}", edge.downstream))
+                        )
+                        .join(" → ")
+                )?;
+                for edge in cycle {
+                    writeln!(f, "  - {}", code(edge))?;
+                }
+            }
+        }
+
+        if !summary.findings.is_empty() {
+            writeln!(f, "\n## Ranked Findings\n")?;
+            writeln!(
+                f,
+                "| # | Severity | Score | Kind | Span | Elapsed | Actor | Hints |"
+            )?;
+            writeln!(f, "|---:|---|---:|---|---|---:|---:|---|")?;
+            for (rank, finding) in summary
+                .findings
+                .iter()
+                .take(RANKED_FINDINGS_LIMIT)
+                .enumerate()
+            {
+                writeln!(
+                    f,
+                    "| {} | {} | {:.1} | {} | {} | {} | {} | {} |",
+                    rank + 1,
+                    finding.severity,
+                    finding.score,
+                    finding.kind,
+                    cell(code(&finding.span)),
+                    secs(finding.elapsed_ns),
+                    finding.actor_id,
+                    cell(finding.hints.iter().map(|hint| &hint.title).join("; "))
+                )?;
+            }
+            if summary.findings.len() > RANKED_FINDINGS_LIMIT {
+                writeln!(
+                    f,
+                    "\n... and {} more findings",
+                    summary.findings.len() - RANKED_FINDINGS_LIMIT
+                )?;
+            }
+        }
+
+        let fast_children_groups = summary.fast_children_groups();
+        if !fast_children_groups.is_empty() {
+            writeln!(f, "\n## Fast Children Actors\n")?;
+            for (group, bottlenecks) in fast_children_groups {
+                let title = if group.actor_ids.len() == 1 {
+                    format!("Actor {}", actor_ids(&group.actor_ids))
+                } else {
+                    format!(
+                        "{} actors: {}",
+                        group.actor_ids.len(),
+                        actor_ids(&group.actor_ids)
+                    )
+                };
+                let mut render = group
+                    .render()
+                    .options(summary.render_options)
+                    .slow_threshold(summary.slow_threshold.threshold_ns);
+                for path in bottlenecks {
+                    render = render.bottleneck(path);
+                }
+                writeln!(f, "<details>\n<summary>{}</summary>\n", title)?;
+                writeln!(f, "```text\n{}```\n", render)?;
+                writeln!(f, "</details>\n")?;
+            }
+        }
+
+        if !summary.join_amplifications.is_empty() {
+            writeln!(f, "\n## Join Amplification\n")?;
+            writeln!(
+                f,
+                "| Job | Join | Operator | Fragment actors | Upstream join actors | Downstream actors |"
+            )?;
+            writeln!(f, "|---|---|---:|---|---|---|")?;
+            for join in &summary.join_amplifications {
+                writeln!(
+                    f,
+                    "| {} | {} | {} | {} | {} | {} |",
+                    cell(code(&join.job_name)),
+                    cell(code(&join.join_executor)),
+                    join.operator_id,
+                    actor_ids(&join.fragment_actors),
+                    actor_ids(&join.upstream_actors),
+                    actor_ids(&join.downstream_actors)
+                )?;
+            }
+        }
+
+        let io_bound_groups = summary.io_bound_groups();
+        if !io_bound_groups.is_empty() {
+            writeln!(f, "\n## IO Bound Actors\n")?;
+            writeln!(f, "| IO span | Job | Actors |")?;
+            writeln!(f, "|---|---|---|")?;
+            for (io_info, actor_names) in io_bound_groups {
+                for (actor_name, ids) in actor_names {
+                    writeln!(
+                        f,
+                        "| {} | {} | {} |",
+                        cell(code(io_info)),
+                        cell(code(actor_name)),
+                        actor_ids(&ids)
+                    )?;
+                }
+            }
+        }
+
+        if !summary.bottleneck_found() {
+            writeln!(f, "\nNo bottleneck actors detected.")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    use crate::await_tree::{AnalyzeSummary, TreeView};
+
+    #[test]
+    fn test_markdown_report() -> Result<()> {
+        let bottleneck = r#"Actor 2: `SELECT a | b FROM t` [1000.000s]
+  Epoch 8251479171792896 [!!! 900.000s]
+    Materialize 200000007 [!!! 900.000s]
+      HashAgg 200000005 [!!! 900.000s]
+        Merge 200000004 [0.001s]
+"#;
+        let io_bound = r#"Actor 3: `SELECT a | b FROM t` [1000.000s]
+  Epoch 8251479171792896 [!!! 900.000s]
+    Materialize 300000007 [!!! 900.000s]
+      store_get [!!! 900.000s]
+"#;
+        let trees = BTreeMap::from([
+            (2, TreeView::from_str(bottleneck).unwrap()),
+            (3, TreeView::from_str(io_bound).unwrap()),
+        ]);
+        let markdown = AnalyzeSummary::from_trees(&trees).markdown().to_string();
+
+        assert!(markdown.starts_with("# Analyze Summary\n"));
+        assert!(markdown.contains("| Epoch age | 2 | 900.000s |"));
+        assert!(markdown.contains("<summary>Actor 2</summary>"));
+        assert!(markdown.contains("      HashAgg 200000005 [!!! 900.000s]  <== bottleneck\n"));
+        assert!(markdown.contains("| `store_get` | `SELECT a \\| b FROM t` | 3 |"));
+        Ok(())
+    }
+}
@@ -41,10 +41,45 @@
 //! ```
 
 mod analyze;
+mod baseline;
+mod blame;
+mod current;
+mod diff;
+mod dot;
+mod finding;
+mod folded;
+mod graph;
+mod html;
+mod knowledge;
+mod markdown;
+mod render;
+mod series;
+mod shape;
+mod stats;
+mod trace;
 mod transcribe;
 mod tree;
 pub(crate) mod utils;
+mod verdict;
 
 pub use analyze::*;
+pub use baseline::*;
+pub use blame::*;
+pub use current::*;
+pub use diff::*;
+pub use dot::*;
+pub use finding::*;
+pub use folded::*;
+pub use graph::*;
+pub use html::*;
+pub use knowledge::*;
+pub use markdown::*;
+pub use render::*;
+pub use series::*;
+pub use shape::*;
+pub use stats::*;
+pub use trace::*;
 pub use transcribe::*;
 pub use tree::*;
+pub use utils::{extract_actor_traces, parse_actor_trees};
+pub use verdict::*;
@@ -0,0 +1,343 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter, Write};
+use std::io::IsTerminal;
+
+use itertools::Itertools;
+
+use crate::await_tree::tree::{SpanNodeView, TreeView, SLOW_SPAN_NS};
+
+const RED: &str = "\x1b[31m";
+const DIM: &str = "\x1b[2m";
+const CYAN: &str = "\x1b[36m";
+const HIGHLIGHT: &str = "\x1b[1;30;43m";
+const RESET: &str = "\x1b[0m";
+
+/// Width of the elapsed time bar of a span as long as the root span.
+const BAR_WIDTH: u128 = 20;
+
+/// Options to keep the rendering of huge trees readable, e.g. with deep Union/Merge fan-ins.
+/// Hidden spans are summarized as `… N spans hidden` lines, which mention the bottleneck and
+/// current spans among them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Only shows the paths leading to slow, bottleneck or current spans.
+    pub prune_fast: bool,
+    /// Hides the spans deeper than this, counting from the root span at depth 0.
+    pub max_depth: Option<usize>,
+    /// Only shows this many of the slowest children of each span.
+    pub max_children: Option<usize>,
+}
+
+/// Text rendering of a [`TreeView`], in the same format as the await-tree dump.
+///
+/// Created by [`TreeView::render`]. Spans on the given bottleneck paths are marked with
+/// `<== bottleneck`. With [`color`](TreeRender::color), slow spans are printed in red,
+/// long-running spans dimmed and bottleneck spans highlighted, followed by elapsed time bars.
+pub struct TreeRender<'a> {
+    tree: &'a TreeView,
+    bottlenecks: Vec<&'a [usize]>,
+    elapsed_ranges: Option<&'a BTreeMap<Vec<usize>, (u128, u128)>>,
+    color: bool,
+    options: RenderOptions,
+    slow_ns: u128,
+}
+
+impl TreeView {
+    pub fn render(&self) -> TreeRender<'_> {
+        TreeRender {
+            tree: self,
+            bottlenecks: Vec::new(),
+            elapsed_ranges: None,
+            color: false,
+            options: RenderOptions::default(),
+            slow_ns: SLOW_SPAN_NS,
+        }
+    }
+}
+
+/// Whether to print ANSI colors to stdout: it must be a terminal, and the `NO_COLOR`
+/// environment variable must be unset or empty, see <https://no-color.org>.
+pub fn stdout_supports_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && std::io::stdout().is_terminal()
+}
+
+impl<'a> TreeRender<'a> {
+    /// Marks the span at `path`, given as child indices from the root span, as a bottleneck.
+    pub fn bottleneck(mut self, path: &'a [usize]) -> Self {
+        self.bottlenecks.push(path);
+        self
+    }
+
+    /// Prints the given `(min, max)` elapsed time of the spans of the main tree instead of
+    /// their own, keyed by span path. See [`ShapeGroup`](crate::await_tree::ShapeGroup).
+    pub fn elapsed_ranges(mut self, ranges: &'a BTreeMap<Vec<usize>, (u128, u128)>) -> Self {
+        self.elapsed_ranges = Some(ranges);
+        self
+    }
+
+    /// Prints ANSI colors and elapsed time bars, e.g. with [`stdout_supports_color`].
+    pub fn color(mut self, enabled: bool) -> Self {
+        self.color = enabled;
+        self
+    }
+
+    pub fn options(mut self, options: RenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Marks the spans pending for at least `slow_ns` as slow, e.g. with
+    /// [`AnalyzeSummary::slow_threshold_ns`](crate::await_tree::AnalyzeSummary::slow_threshold_ns).
+    /// Defaults to 10 seconds.
+    pub fn slow_threshold(mut self, slow_ns: u128) -> Self {
+        self.slow_ns = slow_ns;
+        self
+    }
+
+    /// Writes `text` in the given style if colors are enabled.
+    fn paint(&self, f: &mut Formatter<'_>, style: Option<&str>, text: &str) -> std::fmt::Result {
+        match style {
+            Some(style) if self.color => write!(f, "{}{}{}", style, text, RESET),
+            _ => f.write_str(text),
+        }
+    }
+
+    /// Returns the `(min, max)` elapsed time printed for the span at `path`.
+    fn elapsed_range(&self, node: &SpanNodeView, path: &[usize], attached: bool) -> (u128, u128) {
+        self.elapsed_ranges
+            .filter(|_| attached)
+            .and_then(|ranges| ranges.get(path).copied())
+            .unwrap_or((node.elapsed_ns, node.elapsed_ns))
+    }
+
+    fn is_slow(&self, node: &SpanNodeView, path: &[usize], attached: bool) -> bool {
+        let (_, max_ns) = self.elapsed_range(node, path, attached);
+        !node.span.is_long_running && max_ns >= self.slow_ns
+    }
+
+    /// Whether the span at `path` is kept by [`RenderOptions::prune_fast`], i.e. it is slow, a
+    /// bottleneck or the current span, or one of its descendants is.
+    fn leads_to_slow(&self, node: &SpanNodeView, path: &mut Vec<usize>, attached: bool) -> bool {
+        if self.is_slow(node, path, attached)
+            || (!path.is_empty() && node.id == self.tree.current)
+            || (attached
+                && self
+                    .bottlenecks
+                    .iter()
+                    .any(|bottleneck| bottleneck.starts_with(path)))
+        {
+            return true;
+        }
+        node.children.iter().enumerate().any(|(index, child)| {
+            path.push(index);
+            let leads_to_slow = self.leads_to_slow(child, path, attached);
+            path.pop();
+            leads_to_slow
+        })
+    }
+
+    /// Writes a line summarizing the `hidden` children of the span at `path`, e.g.
+    /// `… 2 fast spans hidden` for `("fast ", "")`.
+    fn fmt_hidden(
+        &self,
+        f: &mut Formatter<'_>,
+        depth: usize,
+        path: &mut Vec<usize>,
+        hidden: &[(usize, &SpanNodeView)],
+        attached: bool,
+        (prefix, suffix): (&str, &str),
+    ) -> std::fmt::Result {
+        let count: usize = hidden.iter().map(|(_, node)| node.iter().count()).sum();
+        if count == 0 {
+            return Ok(());
+        }
+        let mut bottlenecks: BTreeSet<&[usize]> = BTreeSet::new();
+        let mut has_current = false;
+        for (index, node) in hidden {
+            path.push(*index);
+            if attached {
+                bottlenecks.extend(
+                    self.bottlenecks
+                        .iter()
+                        .filter(|bottleneck| bottleneck.starts_with(path)),
+                );
+            }
+            path.pop();
+            has_current |= node.iter().any(|node| node.id == self.tree.current);
+        }
+
+        let mut line = format!(
+            "… {} {}{}{} hidden",
+            count,
+            prefix,
+            if count == 1 { "span" } else { "spans" },
+            suffix
+        );
+        let mut including = vec![];
+        match bottlenecks.len() {
+            0 => {}
+            1 => including.push("a bottleneck span".to_owned()),
+            n => including.push(format!("{} bottleneck spans", n)),
+        }
+        if has_current {
+            including.push("the current span".to_owned());
+        }
+        if !including.is_empty() {
+            write!(line, ", including {}", including.join(" and "))?;
+        }
+        f.write_str(&" ".repeat(depth * 2))?;
+        self.paint(f, Some(DIM), &line)?;
+        f.write_char('\n')
+    }
+
+    fn fmt_node(
+        &self,
+        f: &mut Formatter<'_>,
+        node: &SpanNodeView,
+        depth: usize,
+        path: &mut Vec<usize>,
+        attached: bool,
+    ) -> std::fmt::Result {
+        // Indentation
+        f.write_str(&" ".repeat(depth * 2))?;
+
+        let (min_ns, max_ns) = self.elapsed_range(node, path, attached);
+        let (min_secs, max_secs) = (
+            min_ns as f64 / 1_000_000_000.0,
+            max_ns as f64 / 1_000_000_000.0,
+        );
+        let is_slow = self.is_slow(node, path, attached);
+        let is_bottleneck = attached && self.bottlenecks.contains(&path.as_slice());
+
+        // Span name
+        let name_style = if is_bottleneck {
+            Some(HIGHLIGHT)
+        } else if is_slow {
+            Some(RED)
+        } else if node.span.is_long_running {
+            Some(DIM)
+        } else {
+            None
+        };
+        self.paint(f, name_style, &node.span.name)?;
+
+        // Elapsed time
+        let mut elapsed = format!("[{}{:.3}s", if is_slow { "!!! " } else { "" }, min_secs);
+        if max_ns != min_ns {
+            write!(elapsed, " ~ {:.3}s", max_secs)?;
+        }
+        elapsed.push(']');
+        f.write_char(' ')?;
+        self.paint(f, is_slow.then_some(RED), &elapsed)?;
+
+        // Elapsed time bar, relative to the root span
+        if self.color {
+            let width = (max_ns * BAR_WIDTH).div_ceil(self.tree.tree.elapsed_ns.max(1));
+            if width > 0 {
+                f.write_char(' ')?;
+                self.paint(f, Some(CYAN), &"█".repeat(width.min(BAR_WIDTH) as usize))?;
+            }
+        }
+
+        // Current span marker
+        if depth > 0 && node.id == self.tree.current {
+            f.write_str("  <== current")?;
+        }
+
+        // Bottleneck marker, only paths from the main tree are supported
+        if is_bottleneck {
+            f.write_str("  ")?;
+            self.paint(f, Some(HIGHLIGHT), "<== bottleneck")?;
+        }
+
+        f.write_char('\n')?;
+
+        // Format children recursively, ordered by elapsed time and then by position
+        if node.children.is_empty() {
+            return Ok(());
+        }
+        let children = node
+            .children
+            .iter()
+            .enumerate()
+            .sorted_by_key(|(index, n)| (n.elapsed_ns, *index))
+            .collect_vec();
+        if self
+            .options
+            .max_depth
+            .is_some_and(|max_depth| depth >= max_depth)
+        {
+            return self.fmt_hidden(
+                f,
+                depth + 1,
+                path,
+                &children,
+                attached,
+                ("", " below the max depth"),
+            );
+        }
+        let (mut shown, mut fast) = (vec![], vec![]);
+        for (index, child) in children {
+            path.push(index);
+            if !self.options.prune_fast || self.leads_to_slow(child, path, attached) {
+                shown.push((index, child));
+            } else {
+                fast.push((index, child));
+            }
+            path.pop();
+        }
+        self.fmt_hidden(f, depth + 1, path, &fast, attached, ("fast ", ""))?;
+        // The slowest children come last.
+        let capped = match self.options.max_children {
+            Some(max_children) if shown.len() > max_children => {
+                shown.drain(..shown.len() - max_children).collect_vec()
+            }
+            _ => vec![],
+        };
+        self.fmt_hidden(
+            f,
+            depth + 1,
+            path,
+            &capped,
+            attached,
+            ("", " of faster siblings"),
+        )?;
+        for (index, child) in shown {
+            path.push(index);
+            self.fmt_node(f, child, depth + 1, path, attached)?;
+            path.pop();
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for TreeRender<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Format the main tree
+        self.fmt_node(f, &self.tree.tree, 0, &mut Vec::new(), true)?;
+
+        // Format detached spans
+        for node in &self.tree.detached {
+            writeln!(f, "{}", self.tree.detached_header(node))?;
+            self.fmt_node(f, node, 1, &mut Vec::new(), false)?;
+        }
+
+        Ok(())
+    }
+}
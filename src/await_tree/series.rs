@@ -0,0 +1,414 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Analysis of a series of dumps taken from the same cluster, e.g. one per minute while it
+//! is stuck. A single dump can not tell a span that has been pending for a long time from a
+//! span that keeps finishing and restarting, but a series can.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use itertools::Itertools;
+
+use crate::await_tree::tree::TreeView;
+use crate::await_tree::utils::parse_actor_trees;
+
+/// A span is considered to be still pending if its elapsed time grows by at least this
+/// fraction of the wall-clock gap between two snapshots.
+const STUCK_GROWTH_RATIO: f64 = 0.9;
+
+/// How a span behaves across the snapshots of a series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SpanProgress {
+    /// Present in every snapshot, and its elapsed time grows by the wall-clock gap each time.
+    Stuck,
+    /// Present in every snapshot, but its elapsed time was reset at least once, i.e. the
+    /// span finished and was entered again.
+    Restarting,
+    /// Only present in some of the snapshots, i.e. the span finished or is new.
+    Progressing,
+}
+
+/// Elapsed times of one span across the snapshots of a series.
+#[derive(Debug, Clone)]
+pub struct SpanTimeline {
+    /// Span names from the root span down to this span.
+    pub path: Vec<String>,
+    /// Elapsed time in nanoseconds in each snapshot, `None` if the span is absent.
+    pub elapsed_ns: Vec<Option<u128>>,
+    pub progress: SpanProgress,
+}
+
+impl SpanTimeline {
+    /// Whether none of the children of this span is stuck, i.e. it is where the actor waits.
+    fn is_deepest_stuck(&self, stuck_paths: &BTreeSet<&[String]>) -> bool {
+        !stuck_paths
+            .iter()
+            .any(|path| path.len() > self.path.len() && path.starts_with(&self.path))
+    }
+}
+
+/// Timelines of the spans of one actor, excluding long-running spans.
+#[derive(Debug, Clone)]
+pub struct ActorTimeline {
+    pub actor_id: u32,
+    /// Whether the actor is present in each snapshot.
+    pub present: Vec<bool>,
+    pub spans: Vec<SpanTimeline>,
+}
+
+impl ActorTimeline {
+    /// Whether any span of the actor is stuck across the whole series.
+    pub fn is_stuck(&self) -> bool {
+        self.spans
+            .iter()
+            .any(|span| span.progress == SpanProgress::Stuck)
+    }
+
+    /// Returns the stuck spans that have no stuck children.
+    pub fn deepest_stuck_spans(&self) -> Vec<&SpanTimeline> {
+        let stuck = self
+            .spans
+            .iter()
+            .filter(|span| span.progress == SpanProgress::Stuck);
+        let stuck_paths = stuck.clone().map(|span| span.path.as_slice()).collect();
+        stuck
+            .filter(|span| span.is_deepest_stuck(&stuck_paths))
+            .collect()
+    }
+}
+
+/// Analysis of an ordered series of dumps of the same cluster.
+#[derive(Debug, Clone)]
+pub struct SeriesAnalysis {
+    pub snapshot_count: usize,
+    /// Wall-clock gaps between consecutive snapshots in nanoseconds.
+    pub gaps_ns: Vec<u128>,
+    /// Whether the gaps were inferred from the growth of actor uptimes rather than given.
+    pub gaps_inferred: bool,
+    pub actors: BTreeMap<u32, ActorTimeline>,
+}
+
+impl SeriesAnalysis {
+    /// Analyzes the trees of an ordered series of dumps, each keyed by actor id.
+    ///
+    /// If `interval` is `None`, the wall-clock gap between two snapshots is inferred as the
+    /// median growth of the uptime of the actors present in both, which fails if there is no
+    /// such actor.
+    pub fn new(
+        snapshots: &[BTreeMap<u32, TreeView>],
+        interval: Option<Duration>,
+    ) -> anyhow::Result<Self> {
+        if snapshots.len() < 2 {
+            anyhow::bail!("At least two dumps are required, got {}", snapshots.len());
+        }
+        let gaps_ns = snapshots
+            .iter()
+            .tuple_windows()
+            .enumerate()
+            .map(|(i, (a, b))| match interval {
+                Some(interval) => Ok(interval.as_nanos()),
+                None => infer_gap_ns(a, b).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Failed to infer the gap between dumps {} and {}: no actor is present in both, specify the interval",
+                        i + 1,
+                        i + 2
+                    )
+                }),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let actor_ids: BTreeSet<u32> = snapshots
+            .iter()
+            .flat_map(|trees| trees.keys().copied())
+            .collect();
+        let actors = actor_ids
+            .into_iter()
+            .map(|actor_id| {
+                let timeline = actor_timeline(actor_id, snapshots, &gaps_ns);
+                (actor_id, timeline)
+            })
+            .collect();
+
+        Ok(Self {
+            snapshot_count: snapshots.len(),
+            gaps_ns,
+            gaps_inferred: interval.is_none(),
+            actors,
+        })
+    }
+
+    /// Analyzes the contents of an ordered series of dump files.
+    pub fn from_dumps<S: AsRef<str>>(
+        contents: &[S],
+        interval: Option<Duration>,
+    ) -> anyhow::Result<Self> {
+        let snapshots = contents
+            .iter()
+            .map(|content| parse_actor_trees(content.as_ref()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Self::new(&snapshots, interval)
+    }
+
+    /// Returns the actors with at least one stuck span.
+    pub fn stuck_actors(&self) -> impl Iterator<Item = &ActorTimeline> {
+        self.actors.values().filter(|actor| actor.is_stuck())
+    }
+}
+
+/// Infers the wall-clock gap between two snapshots from the median uptime growth of the
+/// actors present in both. Actors restarted in between are ignored. Returns `None` if no actor
+/// is present in both.
+pub(crate) fn infer_gap_ns(
+    a: &BTreeMap<u32, TreeView>,
+    b: &BTreeMap<u32, TreeView>,
+) -> Option<u128> {
+    let growths = a
+        .iter()
+        .filter_map(|(actor_id, tree)| {
+            let later = b.get(actor_id)?.tree.elapsed_ns;
+            later.checked_sub(tree.tree.elapsed_ns)
+        })
+        .sorted()
+        .collect_vec();
+    growths.get(growths.len() / 2).copied()
+}
+
+/// Whether a span has been pending all along between two snapshots `gap_ns` apart, i.e. its
+/// elapsed time grew by about the gap rather than being reset in between.
+pub(crate) fn grew_by_gap(before_ns: u128, after_ns: u128, gap_ns: u128) -> bool {
+    after_ns >= before_ns && (after_ns - before_ns) as f64 >= gap_ns as f64 * STUCK_GROWTH_RATIO
+}
+
+fn actor_timeline(
+    actor_id: u32,
+    snapshots: &[BTreeMap<u32, TreeView>],
+    gaps_ns: &[u128],
+) -> ActorTimeline {
+    let spans_by_path = snapshots
+        .iter()
+        .map(|trees| {
+            trees
+                .get(&actor_id)
+                .map(|tree| tree.spans_by_path())
+                .unwrap_or_default()
+        })
+        .collect_vec();
+
+    let paths: BTreeSet<&Vec<&str>> = spans_by_path.iter().flat_map(|s| s.keys()).collect();
+    let spans = paths
+        .into_iter()
+        .filter(|path| {
+            // Long-running spans, e.g. the actor span, are expected to keep growing.
+            !spans_by_path
+                .iter()
+                .filter_map(|spans| spans.get(*path))
+                .any(|node| node.span.is_long_running)
+        })
+        .map(|path| {
+            let elapsed_ns = spans_by_path
+                .iter()
+                .map(|spans| spans.get(path).map(|node| node.elapsed_ns))
+                .collect_vec();
+            let progress = if elapsed_ns.iter().any(Option::is_none) {
+                SpanProgress::Progressing
+            } else {
+                let stuck = !gaps_ns.is_empty()
+                    && elapsed_ns
+                        .iter()
+                        .tuple_windows()
+                        .zip_eq(gaps_ns)
+                        .all(|((a, b), gap_ns)| grew_by_gap(a.unwrap(), b.unwrap(), *gap_ns));
+                if stuck {
+                    SpanProgress::Stuck
+                } else {
+                    SpanProgress::Restarting
+                }
+            };
+            SpanTimeline {
+                path: path.iter().map(|name| name.to_string()).collect(),
+                elapsed_ns,
+                progress,
+            }
+        })
+        .collect();
+
+    ActorTimeline {
+        actor_id,
+        present: snapshots
+            .iter()
+            .map(|trees| trees.contains_key(&actor_id))
+            .collect(),
+        spans,
+    }
+}
+
+impl Display for SeriesAnalysis {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "------ Time Series Analysis ------")?;
+        writeln!(f, "Snapshots: {}", self.snapshot_count)?;
+        writeln!(
+            f,
+            "Gaps: {}{}",
+            self.gaps_ns
+                .iter()
+                .map(|gap| format!("{:.3}s", *gap as f64 / 1_000_000_000.0))
+                .join(", "),
+            if self.gaps_inferred {
+                " (inferred from actor uptimes)"
+            } else {
+                ""
+            }
+        )?;
+
+        let stuck_actors = self.stuck_actors().collect_vec();
+        let partial_actors = self
+            .actors
+            .values()
+            .filter(|actor| actor.present.contains(&false))
+            .map(|actor| actor.actor_id)
+            .collect_vec();
+        writeln!(
+            f,
+            "Stuck actors: {}, progressing actors: {}",
+            stuck_actors.len(),
+            self.actors.len() - stuck_actors.len()
+        )?;
+        if !partial_actors.is_empty() {
+            writeln!(
+                f,
+                "Actors missing from some snapshots: {:?}",
+                partial_actors
+            )?;
+        }
+
+        if !stuck_actors.is_empty() {
+            writeln!(f, "\n--- Stuck Spans ---")?;
+            for actor in stuck_actors {
+                writeln!(f, ">> Actor {}", actor.actor_id)?;
+                for span in actor.deepest_stuck_spans() {
+                    writeln!(
+                        f,
+                        "  {}: {}",
+                        span.path.last().unwrap(),
+                        span.elapsed_ns
+                            .iter()
+                            .map(|elapsed| format!(
+                                "{:.3}s",
+                                elapsed.unwrap_or(0) as f64 / 1_000_000_000.0
+                            ))
+                            .join(" -> ")
+                    )?;
+                }
+                let restarting = actor
+                    .spans
+                    .iter()
+                    .filter(|span| span.progress == SpanProgress::Restarting)
+                    .count();
+                if restarting > 0 {
+                    writeln!(f, "  ({} restarting spans)", restarting)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use crate::await_tree::{SeriesAnalysis, SpanProgress, TreeView};
+
+    fn snapshot(trees: &[(u32, &str)]) -> BTreeMap<u32, TreeView> {
+        trees
+            .iter()
+            .map(|(actor_id, tree)| (*actor_id, TreeView::from_str(tree).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_stuck_and_restarting_spans() {
+        let first = snapshot(&[
+            (
+                1,
+                "Actor 1: `mv` [100.000s]\n  Epoch 1 [!!! 20.000s]\n    HashAgg 100000005 [!!! 20.000s]\n      Merge 100000004 [0.100s]\n",
+            ),
+            (
+                2,
+                "Actor 2: `mv` [100.000s]\n  Epoch 2 [1.000s]\n    Merge 200000004 [0.500s]\n",
+            ),
+        ]);
+        let second = snapshot(&[
+            (
+                1,
+                "Actor 1: `mv` [160.000s]\n  Epoch 1 [!!! 80.000s]\n    HashAgg 100000005 [!!! 80.000s]\n      Merge 100000004 [0.200s]\n",
+            ),
+            (
+                2,
+                "Actor 2: `mv` [160.000s]\n  Epoch 3 [1.000s]\n    Merge 200000004 [0.500s]\n",
+            ),
+        ]);
+
+        let analysis = SeriesAnalysis::new(&[first, second], None).unwrap();
+        assert_eq!(analysis.gaps_ns, [60_000_000_000]);
+
+        let actor = &analysis.actors[&1];
+        assert!(actor.is_stuck());
+        let deepest = actor.deepest_stuck_spans();
+        assert_eq!(deepest.len(), 1);
+        assert_eq!(deepest[0].path.last().unwrap(), "HashAgg 100000005");
+        let merge = actor
+            .spans
+            .iter()
+            .find(|span| span.path.last().unwrap() == "Merge 100000004")
+            .unwrap();
+        assert_eq!(merge.progress, SpanProgress::Restarting);
+
+        assert!(!analysis.actors[&2].is_stuck());
+
+        let analysis = SeriesAnalysis::new(
+            &[epoch_snapshot(60.0), epoch_snapshot(120.0)],
+            Some(Duration::from_secs(120)),
+        )
+        .unwrap();
+        assert!(!analysis.actors[&1].is_stuck());
+    }
+
+    #[test]
+    fn test_invalid_series() {
+        assert!(SeriesAnalysis::new(&[epoch_snapshot(60.0)], None).is_err());
+
+        // Without a common actor, the gap can not be inferred.
+        let other = snapshot(&[(2, "Actor 2: `mv` [260.000s]\n  Epoch 1 [!!! 120.000s]\n")]);
+        assert!(SeriesAnalysis::new(&[epoch_snapshot(60.0), other.clone()], None).is_err());
+        let analysis = SeriesAnalysis::new(
+            &[epoch_snapshot(60.0), other],
+            Some(Duration::from_secs(60)),
+        )
+        .unwrap();
+        assert!(!analysis.actors[&1].is_stuck());
+        assert_eq!(analysis.actors[&2].present, [false, true]);
+    }
+
+    fn epoch_snapshot(epoch_secs: f64) -> BTreeMap<u32, TreeView> {
+        snapshot(&[(
+            1,
+            &format!("Actor 1: `mv` [200.000s]\n  Epoch 1 [!!! {epoch_secs:.3}s]\n"),
+        )])
+    }
+}
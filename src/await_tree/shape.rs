@@ -0,0 +1,154 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::await_tree::render::TreeRender;
+use crate::await_tree::tree::TreeView;
+use crate::await_tree::utils::normalize_span_name;
+
+/// Structure of an actor tree with span ids, epochs and elapsed times normalized away.
+///
+/// Parallel actors of the same fragment usually have trees of the same shape.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TreeShape {
+    job_name: Option<String>,
+    /// `(depth, span kind)` of the spans of the main tree in pre-order.
+    spans: Vec<(usize, String)>,
+}
+
+impl TreeView {
+    pub fn shape(&self) -> TreeShape {
+        TreeShape {
+            job_name: self.job_name().map(str::to_owned),
+            spans: self
+                .tree
+                .iter_with_path()
+                .map(|(path, node)| (path.len(), normalize_span_name(&node.span.name)))
+                .collect(),
+        }
+    }
+}
+
+/// Actors whose trees have the same [`TreeShape`], represented by the tree of the first one.
+#[derive(Debug, Clone)]
+pub struct ShapeGroup<'a> {
+    pub actor_ids: BTreeSet<u32>,
+    pub representative: &'a TreeView,
+    /// Minimum and maximum elapsed time of each span across the actors, keyed by the path of
+    /// the span given as child indices from the root span.
+    pub elapsed_ranges: BTreeMap<Vec<usize>, (u128, u128)>,
+}
+
+impl<'a> ShapeGroup<'a> {
+    /// Groups the trees by shape. Groups are ordered by the first appearance of their shape.
+    pub fn group<I>(trees: I) -> Vec<ShapeGroup<'a>>
+    where
+        I: IntoIterator<Item = (u32, &'a TreeView)>,
+    {
+        let mut groups: Vec<ShapeGroup<'a>> = Vec::new();
+        let mut group_index: HashMap<TreeShape, usize> = HashMap::new();
+        for (actor_id, tree) in trees {
+            let index = *group_index.entry(tree.shape()).or_insert_with(|| {
+                groups.push(ShapeGroup {
+                    actor_ids: BTreeSet::new(),
+                    representative: tree,
+                    elapsed_ranges: BTreeMap::new(),
+                });
+                groups.len() - 1
+            });
+            let group = &mut groups[index];
+            group.actor_ids.insert(actor_id);
+            for (path, node) in tree.tree.iter_with_path() {
+                let (min, max) = group
+                    .elapsed_ranges
+                    .entry(path)
+                    .or_insert((node.elapsed_ns, node.elapsed_ns));
+                *min = (*min).min(node.elapsed_ns);
+                *max = (*max).max(node.elapsed_ns);
+            }
+        }
+        groups
+    }
+
+    /// Renders the representative tree with the elapsed time range of each span.
+    pub fn render(&self) -> TreeRender<'_> {
+        self.representative
+            .render()
+            .elapsed_ranges(&self.elapsed_ranges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::str::FromStr;
+
+    use crate::await_tree::tree::SLOW_SPAN_NS;
+    use crate::await_tree::{ShapeGroup, TreeView};
+
+    #[test]
+    fn test_group_by_shape() -> Result<()> {
+        let actor_1 = TreeView::from_str(
+            r#"Actor 1: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 12.000s]
+    HashAgg 100000005 [!!! 12.000s]
+      Merge 100000004 [0.001s]
+"#,
+        )
+        .unwrap();
+        let actor_2 = TreeView::from_str(
+            r#"Actor 2: `mv` [100.000s]
+  Epoch 8251479237365760 [!!! 20.000s]
+    HashAgg 200000005 [!!! 20.000s]
+      Merge 200000004 [0.003s]
+"#,
+        )
+        .unwrap();
+        let actor_3 = TreeView::from_str(
+            r#"Actor 3: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 12.000s]
+    HashAgg 300000005 [!!! 12.000s]
+      store_get [!!! 12.000s]
+"#,
+        )
+        .unwrap();
+        let groups = ShapeGroup::group([(1, &actor_1), (2, &actor_2), (3, &actor_3)]);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(
+            groups[0].actor_ids.iter().copied().collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(
+            groups[0].elapsed_ranges[&vec![0, 0]],
+            (12_000_000_000, 20_000_000_000)
+        );
+        assert!(groups[0]
+            .render()
+            .to_string()
+            .contains("HashAgg 100000005 [!!! 12.000s ~ 20.000s]"));
+        assert_eq!(groups[1].actor_ids.iter().copied().collect::<Vec<_>>(), [3]);
+
+        // The grouped output can be parsed back, with the maximum of each range.
+        let parsed = TreeView::from_str(&groups[0].render().to_string()).unwrap();
+        assert_eq!(parsed.shape(), actor_1.shape());
+        assert_eq!(
+            parsed.tree.children[0].children[0].elapsed_ns,
+            20_000_000_000
+        );
+        assert!(parsed.tree.children[0].children[0].is_slower_than(SLOW_SPAN_NS));
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+/// Width of the longest bar in the histogram.
+const HISTOGRAM_WIDTH: usize = 40;
+
+/// Distribution of elapsed times across actors, e.g. actor uptimes or `Epoch` span ages.
+///
+/// A bimodal distribution of actor uptimes usually means that only part of the cluster has
+/// been recovered.
+#[derive(Debug, Clone, Default)]
+pub struct Distribution {
+    /// `(elapsed_ns, actor_id)` in ascending order.
+    values: Vec<(u128, u32)>,
+}
+
+impl Distribution {
+    pub fn new(values: &BTreeSet<(u128, u32)>) -> Self {
+        Self {
+            values: values.iter().copied().collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn min(&self) -> Option<u128> {
+        self.values.first().map(|(elapsed_ns, _)| *elapsed_ns)
+    }
+
+    pub fn max(&self) -> Option<u128> {
+        self.values.last().map(|(elapsed_ns, _)| *elapsed_ns)
+    }
+
+    /// Returns the `p`-th percentile with the nearest-rank method, `p` in `[0, 100]`.
+    pub fn percentile(&self, p: f64) -> Option<u128> {
+        if self.values.is_empty() {
+            return None;
+        }
+        let rank = (p / 100.0 * self.values.len() as f64).ceil() as usize;
+        Some(self.values[rank.clamp(1, self.values.len()) - 1].0)
+    }
+
+    /// Returns the values outside the Tukey fences of the log-scaled distribution, which are
+    /// also at least 2x away from the median.
+    pub fn outliers(&self) -> Vec<(u128, u32)> {
+        let (Some(q1), Some(median), Some(q3)) = (
+            self.percentile(25.0),
+            self.percentile(50.0),
+            self.percentile(75.0),
+        ) else {
+            return vec![];
+        };
+        let log = |ns: u128| (ns.max(1) as f64).ln();
+        let iqr = log(q3) - log(q1);
+        let (low, high) = (log(q1) - 1.5 * iqr, log(q3) + 1.5 * iqr);
+        self.values
+            .iter()
+            .filter(|(elapsed_ns, _)| {
+                let value = log(*elapsed_ns);
+                (value < low || value > high) && (value - log(median)).abs() >= 2f64.ln()
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Counts the values in buckets of doubling width in seconds, from the bucket containing
+    /// the minimum to the one containing the maximum. Returns `(lower_ns, upper_ns, count)`.
+    ///
+    /// Zero values are counted in the first bucket, whose lower bound is then zero.
+    pub fn log_histogram(&self) -> Vec<(u128, u128, usize)> {
+        let (Some(min), Some(max)) = (self.min(), self.max()) else {
+            return vec![];
+        };
+        let Some(min_nonzero) = self
+            .values
+            .iter()
+            .map(|(elapsed_ns, _)| *elapsed_ns)
+            .find(|elapsed_ns| *elapsed_ns > 0)
+        else {
+            return vec![(0, 1, self.len())];
+        };
+        // Start from the power of two seconds below the minimum, e.g. 8s for 11.455s.
+        let mut lower = 2f64.powi(secs(min_nonzero).log2().floor() as i32);
+        let mut buckets = vec![];
+        while lower <= secs(max) {
+            let upper = lower * 2.0;
+            let first = buckets.is_empty() && min == 0;
+            let count = self
+                .values
+                .iter()
+                .filter(|(elapsed_ns, _)| {
+                    (lower..upper).contains(&secs(*elapsed_ns)) || (first && *elapsed_ns == 0)
+                })
+                .count();
+            buckets.push((
+                if first {
+                    0
+                } else {
+                    (lower * 1_000_000_000.0) as u128
+                },
+                (upper * 1_000_000_000.0) as u128,
+                count,
+            ));
+            lower = upper;
+        }
+        buckets
+    }
+}
+
+/// Aggregated statistics of one span kind across all actors, see
+/// [`AnalyzeSummary::top_span_kinds`](crate::await_tree::AnalyzeSummary::top_span_kinds).
+#[derive(Debug, Clone, Default)]
+pub struct SpanKindStats {
+    /// Number of spans of this kind.
+    pub count: usize,
+    /// Total elapsed time of the spans in nanoseconds.
+    pub total_ns: u128,
+    /// Maximum elapsed time of the spans in nanoseconds.
+    pub max_ns: u128,
+    /// Actors with at least one span of this kind.
+    pub actors: BTreeSet<u32>,
+}
+
+impl SpanKindStats {
+    pub(crate) fn add(&mut self, actor_id: u32, elapsed_ns: u128) {
+        self.count += 1;
+        self.total_ns += elapsed_ns;
+        self.max_ns = self.max_ns.max(elapsed_ns);
+        self.actors.insert(actor_id);
+    }
+
+    pub(crate) fn merge(&mut self, other: &SpanKindStats) {
+        self.count += other.count;
+        self.total_ns += other.total_ns;
+        self.max_ns = self.max_ns.max(other.max_ns);
+        self.actors.extend(&other.actors);
+    }
+}
+
+impl Display for SpanKindStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        const MAX_ACTORS_SHOWN: usize = 8;
+        write!(
+            f,
+            "count {}, total {:.3}s, max {:.3}s, actors {:?}",
+            self.count,
+            secs(self.total_ns),
+            secs(self.max_ns),
+            self.actors
+                .iter()
+                .take(MAX_ACTORS_SHOWN)
+                .collect::<Vec<_>>()
+        )?;
+        if self.actors.len() > MAX_ACTORS_SHOWN {
+            write!(f, " and {} more", self.actors.len() - MAX_ACTORS_SHOWN)?;
+        }
+        Ok(())
+    }
+}
+
+fn secs(elapsed_ns: u128) -> f64 {
+    elapsed_ns as f64 / 1_000_000_000.0
+}
+
+impl Display for Distribution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (Some(min), Some(max)) = (self.min(), self.max()) else {
+            return writeln!(f, "Count: 0");
+        };
+        writeln!(f, "Count: {}", self.len())?;
+        writeln!(f, "Min: {:.3}s", secs(min))?;
+        for p in [50.0, 90.0, 99.0] {
+            writeln!(f, "P{}: {:.3}s", p, secs(self.percentile(p).unwrap()))?;
+        }
+        writeln!(f, "Max: {:.3}s", secs(max))?;
+
+        writeln!(f, "Histogram:")?;
+        let histogram = self.log_histogram();
+        let max_count = histogram
+            .iter()
+            .map(|(_, _, count)| *count)
+            .max()
+            .unwrap_or(1);
+        for (lower, upper, count) in histogram {
+            let width = (count * HISTOGRAM_WIDTH).div_ceil(max_count);
+            writeln!(
+                f,
+                "  [{:>10.3}s, {:>10.3}s) {:<width$} {}",
+                secs(lower),
+                secs(upper),
+                "#".repeat(width),
+                count,
+                width = HISTOGRAM_WIDTH
+            )?;
+        }
+
+        let outliers = self.outliers();
+        if !outliers.is_empty() {
+            writeln!(f, "Outliers:")?;
+            for (elapsed_ns, actor_id) in outliers {
+                writeln!(f, "  Actor {}: {:.3}s", actor_id, secs(elapsed_ns))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::await_tree::Distribution;
+
+    #[test]
+    fn test_percentiles_and_outliers() {
+        let values: BTreeSet<(u128, u32)> = (1..=10)
+            .map(|actor_id| (10_000_000_000 + actor_id as u128, actor_id))
+            .chain([(3_000_000_000_000, 11)])
+            .collect();
+        let distribution = Distribution::new(&values);
+
+        assert_eq!(distribution.percentile(50.0), Some(10_000_000_006));
+        assert_eq!(distribution.percentile(99.0), Some(3_000_000_000_000));
+        assert_eq!(distribution.outliers(), [(3_000_000_000_000, 11)]);
+
+        let histogram = distribution.log_histogram();
+        assert_eq!(histogram.first().unwrap().2, 10);
+        assert_eq!(histogram.last().unwrap().2, 1);
+        assert_eq!(
+            histogram.iter().map(|(_, _, count)| count).sum::<usize>(),
+            11
+        );
+    }
+
+    #[test]
+    fn test_histogram_with_zero() {
+        let values: BTreeSet<(u128, u32)> =
+            BTreeSet::from([(0, 1), (0, 2), (1_500_000_000, 3), (5_000_000_000, 4)]);
+        let histogram = Distribution::new(&values).log_histogram();
+        assert_eq!(
+            histogram,
+            [
+                (0, 2_000_000_000, 3),
+                (2_000_000_000, 4_000_000_000, 0),
+                (4_000_000_000, 8_000_000_000, 1),
+            ]
+        );
+
+        let zeros = BTreeSet::from([(0, 1), (0, 2)]);
+        assert_eq!(Distribution::new(&zeros).log_histogram(), [(0, 1, 2)]);
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export of a dump in the Chrome Trace Event format, which can be opened in Perfetto or
+//! `chrome://tracing`.
+//!
+//! A dump only has the elapsed time of each span, so a span is assumed to have started its
+//! elapsed time before the dump was taken. Spans that are polled concurrently therefore show
+//! up nested in the one that started earlier.
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::await_tree::tree::{SpanNodeView, TreeView};
+use crate::await_tree::utils::parse_traces;
+
+#[derive(Debug, Clone, Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: &'static str,
+    ph: &'static str,
+    /// Start time in microseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ts: Option<u128>,
+    /// Duration in microseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<u128>,
+    pid: usize,
+    tid: u32,
+    args: serde_json::Value,
+}
+
+/// Chrome trace of a dump, with one process per job and one track per actor.
+pub struct ChromeTrace<'a> {
+    trees: &'a BTreeMap<u32, TreeView>,
+    /// Time the dump was taken in microseconds. Defaults to the elapsed time of the longest
+    /// span, so that the trace starts at zero.
+    dump_time_us: Option<u128>,
+}
+
+impl<'a> ChromeTrace<'a> {
+    pub fn new(trees: &'a BTreeMap<u32, TreeView>) -> Self {
+        Self {
+            trees,
+            dump_time_us: None,
+        }
+    }
+
+    /// Sets the wall-clock time the dump was taken, so that the trace can be lined up with
+    /// other traces and logs.
+    pub fn dump_time(mut self, dump_time: SystemTime) -> Self {
+        self.dump_time_us = dump_time
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|since_epoch| since_epoch.as_micros());
+        self
+    }
+
+    fn events(&self) -> Vec<TraceEvent> {
+        let dump_time_us = self.dump_time_us.unwrap_or_else(|| {
+            self.trees
+                .values()
+                .flat_map(|tree| std::iter::once(&tree.tree).chain(&tree.detached))
+                .map(|node| node.elapsed_ns / 1_000)
+                .max()
+                .unwrap_or(0)
+        });
+        let mut jobs: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut events = vec![];
+        for (actor_id, tree) in self.trees {
+            let job_name = tree.job_name().unwrap_or("unknown");
+            let next_pid = jobs.len() + 1;
+            let pid = *jobs.entry(job_name).or_insert_with(|| {
+                events.push(metadata("process_name", next_pid, 0, job_name));
+                next_pid
+            });
+            events.push(metadata(
+                "thread_name",
+                pid,
+                *actor_id,
+                &format!("Actor {}", actor_id),
+            ));
+            let mut span_events = vec![];
+            let track = Track {
+                pid,
+                tid: *actor_id,
+                dump_time_us,
+            };
+            track.collect(&tree.tree, false, &mut span_events);
+            for node in &tree.detached {
+                track.collect(node, true, &mut span_events);
+            }
+            // Parents before children, as expected for events with the same start time.
+            span_events.sort_by_key(|event| (event.ts, std::cmp::Reverse(event.dur)));
+            events.extend(span_events);
+        }
+        events
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        serde_json::to_string(&json!({
+            "traceEvents": self.events(),
+            "displayTimeUnit": "ms",
+        }))
+        .map_err(|e| anyhow::anyhow!("Failed to serialize chrome trace: {}", e))
+    }
+}
+
+/// Builds the trace of the actor traces of a dump, as returned by
+/// [`extract_actor_traces`](crate::await_tree::extract_actor_traces).
+pub fn chrome_trace_from_traces<'a, M>(actor_traces: M) -> anyhow::Result<String>
+where
+    M: IntoIterator<Item = (&'a u32, &'a String)>,
+{
+    ChromeTrace::new(&parse_traces(actor_traces)?).to_json()
+}
+
+fn metadata(name: &str, pid: usize, tid: u32, value: &str) -> TraceEvent {
+    TraceEvent {
+        name: name.to_owned(),
+        cat: "__metadata",
+        ph: "M",
+        ts: None,
+        dur: None,
+        pid,
+        tid,
+        args: json!({ "name": value }),
+    }
+}
+
+/// The track of one actor.
+struct Track {
+    pid: usize,
+    tid: u32,
+    dump_time_us: u128,
+}
+
+impl Track {
+    fn collect(&self, node: &SpanNodeView, detached: bool, events: &mut Vec<TraceEvent>) {
+        let dur = node.elapsed_ns / 1_000;
+        events.push(TraceEvent {
+            name: node.span.name.clone(),
+            cat: "await",
+            ph: "X",
+            ts: Some(self.dump_time_us.saturating_sub(dur)),
+            dur: Some(dur),
+            pid: self.pid,
+            tid: self.tid,
+            args: json!({
+                "elapsed_ns": node.elapsed_ns.to_string(),
+                "long_running": node.span.is_long_running,
+                "detached": detached,
+            }),
+        });
+        for child in &node.children {
+            self.collect(child, detached, events);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    use crate::await_tree::{ChromeTrace, TreeView};
+
+    #[test]
+    fn test_chrome_trace() -> Result<()> {
+        let tree = r#"Actor 2: `mv` [100.000s]
+  Epoch 8251479171792896 [!!! 60.000s]
+    HashAgg 200000005 [!!! 60.000s]
+      store_get [0.500s]
+"#;
+        let trees = BTreeMap::from([(2, TreeView::from_str(tree).unwrap())]);
+        let trace: serde_json::Value = serde_json::from_str(&ChromeTrace::new(&trees).to_json()?)?;
+        let events = trace["traceEvents"].as_array().unwrap();
+
+        assert_eq!(events[0]["args"]["name"], "mv");
+        assert_eq!(events[1]["args"]["name"], "Actor 2");
+        let spans = events[2..]
+            .iter()
+            .map(|event| {
+                (
+                    event["name"].as_str().unwrap(),
+                    event["ts"].as_u64().unwrap(),
+                    event["dur"].as_u64().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            [
+                ("Actor 2: `mv`", 0, 100_000_000),
+                ("Epoch 8251479171792896", 40_000_000, 60_000_000),
+                ("HashAgg 200000005", 40_000_000, 60_000_000),
+                ("store_get", 99_500_000, 500_000),
+            ]
+        );
+        Ok(())
+    }
+}
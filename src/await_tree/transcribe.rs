@@ -12,10 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::await_tree::render::RenderOptions;
 use crate::await_tree::tree::TreeView;
 use crate::await_tree::utils::extract_actor_traces;
 
 pub fn transcribe(path: String) -> anyhow::Result<()> {
+    transcribe_with_options(path, &RenderOptions::default())
+}
+
+/// Same as [`transcribe`], but prunes the printed trees with the given options.
+pub fn transcribe_with_options(path: String, options: &RenderOptions) -> anyhow::Result<()> {
     let content =
         std::fs::read_to_string(path).map_err(|e| anyhow::anyhow!("Failed to read file: {}", e))?;
     let actor_traces = extract_actor_traces(&content)
@@ -24,7 +30,7 @@ pub fn transcribe(path: String) -> anyhow::Result<()> {
         let tree: TreeView = serde_json::from_str(&trace)
             .map_err(|e| anyhow::anyhow!("Failed to parse actor trace JSON: {}", e))?;
         println!(">> Actor {}", actor_id);
-        println!("{}", tree);
+        println!("{}", tree.render().options(*options));
     }
     Ok(())
 }
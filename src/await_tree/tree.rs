@@ -12,10 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::fmt::Write;
-
-use itertools::Itertools;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::str::FromStr;
 
 /// See <https://github.com/risingwavelabs/await-tree/blob/main/src/context.rs> for the original definition.
@@ -30,6 +28,11 @@ pub struct TreeView {
 
     /// Detached subtrees
     pub(crate) detached: Vec<SpanNodeView>,
+
+    /// Whether the span ids were assigned while parsing the text format, rather than taken
+    /// from the traced process.
+    #[serde(skip)]
+    pub(crate) made_up_ids: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -47,20 +50,48 @@ pub(crate) struct SpanNodeView {
     pub children: Vec<SpanNodeView>,
 }
 
+/// Default threshold of slow spans in nanoseconds, see [`SpanNodeView::is_slower_than`].
+pub(crate) const SLOW_SPAN_NS: u128 = 10_000_000_000;
+
 impl SpanNodeView {
-    pub fn visit<F>(&self, f: &F) -> bool
-    where
-        F: Fn(&SpanNodeView) -> bool,
-    {
-        if f(self) {
-            return true;
-        }
-        for child in &self.children {
-            if Self::visit(child, f) {
-                return true;
+    /// Whether this span is pending for at least `threshold_ns` without being long-running.
+    pub fn is_slower_than(&self, threshold_ns: u128) -> bool {
+        !self.span.is_long_running && self.elapsed_ns >= threshold_ns
+    }
+
+    /// Average elapsed time of the children in seconds, or `NaN` if there is no child.
+    pub fn children_avg_secs(&self) -> f64 {
+        let sum: f64 = self
+            .children
+            .iter()
+            .map(|child| child.elapsed_ns as f64 / 1_000_000_000.0)
+            .sum();
+        sum / self.children.len() as f64
+    }
+
+    /// Iterates over this node and all its descendants in pre-order.
+    pub fn iter(&self) -> impl Iterator<Item = &SpanNodeView> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            Some(node)
+        })
+    }
+
+    /// Iterates over this node and all its descendants in pre-order, together with their
+    /// paths given as child indices from this node.
+    pub fn iter_with_path(&self) -> impl Iterator<Item = (Vec<usize>, &SpanNodeView)> {
+        let mut stack = vec![(vec![], self)];
+        std::iter::from_fn(move || {
+            let (path, node) = stack.pop()?;
+            for (index, child) in node.children.iter().enumerate().rev() {
+                let mut child_path = path.clone();
+                child_path.push(index);
+                stack.push((child_path, child));
             }
-        }
-        false
+            Some((path, node))
+        })
     }
 
     pub fn visit_all<F>(&self, f: &mut F)
@@ -87,86 +118,89 @@ pub(crate) struct SpanView {
     pub is_long_running: bool,
 }
 
-impl std::fmt::Display for TreeView {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        fn fmt_node(
-            f: &mut std::fmt::Formatter<'_>,
-            node: &SpanNodeView,
-            depth: usize,
-            current_id: usize,
-        ) -> std::fmt::Result {
-            // Indentation
-            f.write_str(&" ".repeat(depth * 2))?;
-
-            // Span name
-            f.write_str(&node.span.name)?;
-
-            // Elapsed time
-            let elapsed_secs = node.elapsed_ns as f64 / 1_000_000_000.0;
-            write!(
-                f,
-                " [{}{:.3}s]",
-                if !node.span.is_long_running && elapsed_secs >= 10.0 {
-                    "!!! "
-                } else {
-                    ""
-                },
-                elapsed_secs
-            )?;
-
-            // Current span marker
-            if depth > 0 && node.id == current_id {
-                f.write_str("  <== current")?;
-            }
-
-            f.write_char('\n')?;
-
-            // Format children recursively
-            for child in node.children.iter().sorted_by_key(|n| n.elapsed_ns) {
-                fmt_node(f, child, depth + 1, current_id)?;
+impl TreeView {
+    /// Returns all spans of the main tree keyed by their span names from the root span, which
+    /// identify the same span across dumps of the same actor.
+    pub(crate) fn spans_by_path(&self) -> BTreeMap<Vec<&str>, &SpanNodeView> {
+        fn collect<'a>(
+            node: &'a SpanNodeView,
+            path: &mut Vec<&'a str>,
+            spans: &mut BTreeMap<Vec<&'a str>, &'a SpanNodeView>,
+        ) {
+            path.push(&node.span.name);
+            spans.insert(path.clone(), node);
+            for child in &node.children {
+                collect(child, path, spans);
             }
+            path.pop();
+        }
+        let mut spans = BTreeMap::new();
+        collect(&self.tree, &mut Vec::new(), &mut spans);
+        spans
+    }
 
-            Ok(())
+    /// Returns the header of a detached subtree, e.g. `[Detached 42: store_get]`. Made-up span
+    /// ids are left out.
+    pub(crate) fn detached_header(&self, root: &SpanNodeView) -> String {
+        if self.made_up_ids {
+            format!("[Detached {}]", root.span.name)
+        } else {
+            format!("[Detached {}: {}]", root.id, root.span.name)
         }
-        // Format the main tree
-        fmt_node(f, &self.tree, 0, self.current)?;
+    }
 
-        // Format detached spans
-        for node in &self.detached {
-            writeln!(f, "[Detached {}]", node.id)?;
-            fmt_node(f, node, 1, self.current)?;
+    /// Returns the span names from the root span down to the span at `path`.
+    pub fn span_names(&self, path: &[usize]) -> Vec<String> {
+        let mut node = &self.tree;
+        let mut names = vec![node.span.name.clone()];
+        for index in path {
+            node = &node.children[*index];
+            names.push(node.span.name.clone());
         }
+        names
+    }
+}
 
-        Ok(())
+impl std::fmt::Display for TreeView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.render().fmt(f)
     }
 }
 
 /// The process of converting the tree to text is not lossless—information such as
 /// `node_id` will be lost. Consequently, this function can only restore information
-/// to the best extent possible. Fields like `current` and `node_id` cannot be
-/// recovered, but this loss does not affect our bottleneck detection. In the
-/// function, we will set all `node_id` values to 0 and `current` to 100.
+/// to the best extent possible. `node_id` cannot be recovered, so we assign sequential
+/// ids in the order of the lines instead. `current` is recovered from the `<== current`
+/// marker, and falls back to the root span if there is no marker, as the marker is never
+/// printed for the root span.
 impl FromStr for TreeView {
     type Err = &'static str;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut tree: Option<SpanNodeView> = None;
-        let mut detached: Vec<SpanNodeView> = Vec::new();
+        // The main tree followed by the detached subtrees.
+        let mut roots: Vec<SpanNodeView> = Vec::new();
         let mut node_stack: Vec<(usize, SpanNodeView)> = Vec::new();
+        let mut next_id = 1;
+        let mut current = None;
+        let mut in_detached = false;
 
         for line in input.lines() {
             let mut line = line.trim_end(); // Remove trailing spaces
 
             // Check for detached span
             if line.starts_with("[Detached ") {
-                if let Some((_, node)) = node_stack.pop() {
-                    detached.push(node);
-                }
+                roots.extend(close_stack(&mut node_stack));
+                in_detached = true;
                 continue;
             }
 
+            if let Some(stripped) = line.strip_suffix("<== bottleneck") {
+                line = stripped.trim_end();
+            }
+            let mut is_current = false;
             if let Some(stripped) = line.strip_suffix("<== current") {
                 line = stripped.trim_end(); // Remove and trim again
+                is_current = true;
             }
 
             // Check for span definition line
@@ -189,20 +223,25 @@ impl FromStr for TreeView {
 
                     let id = node_stack.len();
                     let new_node = SpanNodeView {
-                        id: 0, // id cannot be recovered, we set it to 0
+                        id: next_id, // id cannot be recovered, we assign it sequentially
                         span: span_view,
                         elapsed_ns,
                         children: Vec::new(),
                     };
 
-                    // Determine the depth of the current line (2 spaces per depth level)
-                    let depth = line.chars().take_while(|&c| c == ' ').count() / 2;
+                    if is_current {
+                        current = Some(next_id);
+                    }
+                    next_id += 1;
 
-                    if depth == 0 {
+                    // Determine the depth of the current line (2 spaces per depth level).
+                    // Detached subtrees are indented by one more level.
+                    let depth = (line.chars().take_while(|&c| c == ' ').count() / 2)
+                        .saturating_sub(in_detached as usize);
+
+                    if depth == 0 || node_stack.is_empty() {
                         // Root span
-                        if let Some((_, root)) = node_stack.pop() {
-                            detached.push(root);
-                        }
+                        roots.extend(close_stack(&mut node_stack));
                         node_stack.push((id, new_node));
                     } else {
                         // Check if the depth decreased, pop stack if necessary
@@ -221,37 +260,44 @@ impl FromStr for TreeView {
         }
 
         // Properly build the tree by attaching remaining nodes to their parents
-        while let Some((_, node)) = node_stack.pop() {
-            if let Some((_, parent)) = node_stack.last_mut() {
-                parent.children.push(node);
-            } else {
-                tree = Some(node); // The last node in the stack is the root
-            }
-        }
-
-        if tree.is_none() {
+        roots.extend(close_stack(&mut node_stack));
+        let mut roots = roots.into_iter();
+        let Some(tree) = roots.next() else {
             return Err("Failed to parse tree view");
-        }
+        };
 
         Ok(TreeView {
-            current: usize::MAX, // Always set to an unreachable number
-            tree: tree.unwrap(),
-            detached,
+            current: current.unwrap_or(tree.id),
+            tree,
+            detached: roots.collect(),
+            made_up_ids: true,
         })
     }
 }
 
-/// Parses the elapsed time in nanoseconds from a string.
+/// Attaches the spans on the stack to their parents, and returns the root span at the bottom.
+fn close_stack(node_stack: &mut Vec<(usize, SpanNodeView)>) -> Option<SpanNodeView> {
+    let (_, mut node) = node_stack.pop()?;
+    while let Some((_, mut parent)) = node_stack.pop() {
+        parent.children.push(node);
+        node = parent;
+    }
+    Some(node)
+}
+
+/// Parses the elapsed time in nanoseconds from a string. For the range of a group of actors
+/// rendered by [`ShapeGroup::render`](crate::await_tree::ShapeGroup::render), the maximum is
+/// taken.
 ///
 /// # Example Input:
 /// - "123456789ns"
 /// - "!!! 12.345s"
+/// - "!!! 12.345s ~ 20.000s"
 fn parse_elapsed_ns(s: &str) -> u128 {
-    if s.starts_with("!!!") {
-        let s = s.trim_start_matches("!!!").trim();
-        parse_time_str(s)
-    } else {
-        parse_time_str(s)
+    let s = s.trim_start_matches("!!!").trim();
+    match s.split_once('~') {
+        Some((_, max)) => parse_time_str(max.trim()),
+        None => parse_time_str(s),
     }
 }
 
@@ -278,7 +324,7 @@ mod tests {
     use anyhow::Result;
     use std::str::FromStr;
 
-    use crate::await_tree::TreeView;
+    use crate::await_tree::{RenderOptions, TreeView};
 
     #[test]
     fn test_parse_tree_view_from_text_1() -> Result<()> {
@@ -298,7 +344,7 @@ mod tests {
     Materialize 8400000007 [!!! 21.283s]
       Project 8400000006 [!!! 21.280s]
         HashAgg 8400000005 [!!! 21.280s]
-          Merge 8400000004 [0.001s]
+          Merge 8400000004 [0.001s]  <== current
 "#;
         assert_eq!(tree_view.to_string(), expected);
         Ok(())
@@ -323,10 +369,225 @@ mod tests {
     Materialize 8400000007 [!!! 21.283s]
       Project 8400000006 [!!! 21.280s]
         HashAgg 8400000005 [!!! 21.280s]
-          Merge 8400000004 [0.001s]
+          Merge 8400000004 [0.001s]  <== current
         HashAgg 8400000005 [!!! 21.380s]
 "#;
         assert_eq!(tree_view.to_string(), expected);
         Ok(())
     }
+
+    #[test]
+    fn test_parse_tree_view_with_detached() -> Result<()> {
+        let input = r#"Actor 7: `mv` [30.000s]
+  Epoch 8251479171792896 [!!! 12.000s]
+    Materialize 700000007 [!!! 12.000s]
+      Merge 700000004 [0.100s]
+    HashAgg 700000005 [0.200s]
+[Detached 42]
+  fetch_block [!!! 11.000s]
+    store_get [!!! 11.000s]  <== current
+  sync [1.000s]
+[Detached 43]
+  spill [0.500s]
+"#;
+        let tree_view = TreeView::from_str(input).unwrap();
+        assert_eq!(tree_view.tree.children[0].children.len(), 2);
+        assert_eq!(tree_view.detached.len(), 3);
+        assert_eq!(tree_view.detached[0].children.len(), 1);
+
+        // The made-up span ids are not printed.
+        let expected = r#"Actor 7: `mv` [30.000s]
+  Epoch 8251479171792896 [!!! 12.000s]
+    HashAgg 700000005 [0.200s]
+    Materialize 700000007 [!!! 12.000s]
+      Merge 700000004 [0.100s]
+[Detached fetch_block]
+  fetch_block [!!! 11.000s]
+    store_get [!!! 11.000s]  <== current
+[Detached sync]
+  sync [1.000s]
+[Detached spill]
+  spill [0.500s]
+"#;
+        assert_eq!(tree_view.to_string(), expected);
+        assert_eq!(TreeView::from_str(expected).unwrap().to_string(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_detached_span_ids() -> Result<()> {
+        let span = |id: usize, name: &str, elapsed_ns: u128| {
+            serde_json::json!({
+                "id": id,
+                "span": { "name": name, "is_verbose": false, "is_long_running": false },
+                "elapsed_ns": elapsed_ns,
+                "children": [],
+            })
+        };
+        let json = serde_json::json!({
+            "current": 1,
+            "tree": span(1, "Actor 7: `mv`", 3_000_000_000),
+            "detached": [span(42, "store_get", 2_000_000_000), span(43, "store_get", 1_000_000_000)],
+        });
+        let tree_view: TreeView = serde_json::from_value(json)?;
+
+        // Span ids recorded by the traced process tell sibling subtrees of the same name apart.
+        let expected = r#"Actor 7: `mv` [3.000s]
+[Detached 42: store_get]
+  store_get [2.000s]
+[Detached 43: store_get]
+  store_get [1.000s]
+"#;
+        assert_eq!(tree_view.to_string(), expected);
+        assert_eq!(TreeView::from_str(expected).unwrap().detached.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_bottleneck_marker() -> Result<()> {
+        let input = r#"Actor 132: `mv` [21.285s]
+  Epoch 8251479171792896 [!!! 21.283s]
+    Materialize 8400000007 [!!! 21.283s]
+      HashAgg 8400000005 [!!! 21.280s]  <== bottleneck
+        Merge 8400000004 [0.001s]
+"#;
+        let tree_view = TreeView::from_str(input).unwrap();
+        assert_eq!(tree_view.render().bottleneck(&[0, 0, 0]).to_string(), input);
+        assert_eq!(
+            tree_view.span_names(&[0, 0, 0]),
+            [
+                "Actor 132: `mv`",
+                "Epoch 8251479171792896",
+                "Materialize 8400000007",
+                "HashAgg 8400000005"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_color() -> Result<()> {
+        let input = r#"Actor 132: `mv` [20.000s]
+  Epoch 8251479171792896 [!!! 20.000s]
+    Materialize 8400000007 [!!! 20.000s]
+      Merge 8400000004 [1.000s]
+"#;
+        let mut tree_view = TreeView::from_str(input).unwrap();
+        tree_view.tree.children[0].span.is_long_running = true;
+        let render = tree_view
+            .render()
+            .bottleneck(&[0, 0])
+            .color(true)
+            .to_string();
+        let lines = render.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines[1],
+            "  \x1b[2mEpoch 8251479171792896\x1b[0m [20.000s] \x1b[36m████████████████████\x1b[0m"
+        );
+        assert!(lines[2].starts_with(
+            "    \x1b[1;30;43mMaterialize 8400000007\x1b[0m \x1b[31m[!!! 20.000s]\x1b[0m"
+        ));
+        assert!(lines[2].ends_with("  \x1b[1;30;43m<== bottleneck\x1b[0m"));
+        assert_eq!(lines[3], "      Merge 8400000004 [1.000s] \x1b[36m█\x1b[0m");
+        assert_eq!(
+            tree_view.render().color(false).to_string(),
+            tree_view.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_options() -> Result<()> {
+        let input = r#"Actor 132: `mv` [21.285s]
+  Epoch 8251479171792896 [!!! 21.283s]
+    Materialize 8400000007 [!!! 21.283s]
+      Union 8400000006 [!!! 21.280s]
+        Merge 8400000001 [0.001s]
+          LocalInput (actor 1) [0.001s]
+        Merge 8400000002 [0.002s]
+          LocalInput (actor 2) [0.002s]
+        Merge 8400000003 [!!! 21.280s]
+          LocalInput (actor 3) [!!! 21.280s]
+        Merge 8400000004 [0.004s]  <== current
+        Merge 8400000005 [!!! 12.000s]
+"#;
+        let tree_view = TreeView::from_str(input).unwrap();
+
+        let pruned = tree_view
+            .render()
+            .options(RenderOptions {
+                prune_fast: true,
+                ..Default::default()
+            })
+            .to_string();
+        let expected = r#"Actor 132: `mv` [21.285s]
+  Epoch 8251479171792896 [!!! 21.283s]
+    Materialize 8400000007 [!!! 21.283s]
+      Union 8400000006 [!!! 21.280s]
+        … 4 fast spans hidden
+        Merge 8400000004 [0.004s]  <== current
+        Merge 8400000005 [!!! 12.000s]
+        Merge 8400000003 [!!! 21.280s]
+          LocalInput (actor 3) [!!! 21.280s]
+"#;
+        assert_eq!(pruned, expected);
+
+        let limited = tree_view
+            .render()
+            .options(RenderOptions {
+                prune_fast: false,
+                max_depth: Some(4),
+                max_children: Some(2),
+            })
+            .to_string();
+        let expected = r#"Actor 132: `mv` [21.285s]
+  Epoch 8251479171792896 [!!! 21.283s]
+    Materialize 8400000007 [!!! 21.283s]
+      Union 8400000006 [!!! 21.280s]
+        … 5 spans of faster siblings hidden, including the current span
+        Merge 8400000005 [!!! 12.000s]
+        Merge 8400000003 [!!! 21.280s]
+          … 1 span below the max depth hidden
+"#;
+        assert_eq!(limited, expected);
+
+        // Hidden bottleneck and current spans are mentioned.
+        let bottleneck = [0, 0, 0, 2];
+        let cut = tree_view
+            .render()
+            .bottleneck(&bottleneck)
+            .options(RenderOptions {
+                max_depth: Some(3),
+                ..Default::default()
+            })
+            .to_string();
+        assert!(cut.ends_with(
+            "      Union 8400000006 [!!! 21.280s]\n        … 8 spans below the max depth hidden, including a bottleneck span and the current span\n"
+        ));
+
+        // Spans are slow by the given threshold.
+        let pruned = tree_view
+            .render()
+            .slow_threshold(2_000_000)
+            .options(RenderOptions {
+                prune_fast: true,
+                ..Default::default()
+            })
+            .to_string();
+        let expected = r#"Actor 132: `mv` [21.285s]
+  Epoch 8251479171792896 [!!! 21.283s]
+    Materialize 8400000007 [!!! 21.283s]
+      Union 8400000006 [!!! 21.280s]
+        … 2 fast spans hidden
+        Merge 8400000002 [!!! 0.002s]
+          LocalInput (actor 2) [!!! 0.002s]
+        Merge 8400000004 [!!! 0.004s]  <== current
+        Merge 8400000005 [!!! 12.000s]
+        Merge 8400000003 [!!! 21.280s]
+          LocalInput (actor 3) [!!! 21.280s]
+"#;
+        assert_eq!(pruned, expected);
+        Ok(())
+    }
 }
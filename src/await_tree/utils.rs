@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
 
 use crate::await_tree::tree::TreeView;
@@ -20,7 +20,7 @@ use crate::await_tree::tree::TreeView;
 /// See doc on [`crate::await_tree`] for the format of the trace.
 ///
 /// Returns `actor_id -> trace string`
-pub(crate) fn extract_actor_traces(content: &str) -> anyhow::Result<HashMap<u32, String>> {
+pub fn extract_actor_traces(content: &str) -> anyhow::Result<HashMap<u32, String>> {
     let mut actor_traces = HashMap::new();
     let mut in_actor_traces = false;
     let mut current_actor_id = None;
@@ -87,6 +87,98 @@ pub(crate) fn extract_actor_traces(content: &str) -> anyhow::Result<HashMap<u32,
     Ok(actor_traces)
 }
 
+/// Extracts the upstream actor id from an exchange input span.
+///
+/// # Example Input:
+/// - "LocalInput (actor 122807)"
+/// - "RemoteInput (actor 122807)"
+pub(crate) fn upstream_actor_id(span_name: &str) -> Option<u32> {
+    let rest = span_name
+        .strip_prefix("LocalInput")
+        .or_else(|| span_name.strip_prefix("RemoteInput"))?;
+    rest.trim()
+        .strip_prefix("(actor ")?
+        .strip_suffix(')')?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Normalizes a span name into its kind, so that spans of different actors, executors and
+/// epochs can be aggregated.
+///
+/// # Example Input:
+/// - "Actor 17: `mv`" → "Actor"
+/// - "Epoch 8318328637423616" → "Epoch"
+/// - "HashAgg 1100000005" → "HashAgg"
+/// - "LocalInput (actor 122807)" → "LocalInput"
+/// - "store_get" → "store_get"
+pub(crate) fn normalize_span_name(span_name: &str) -> String {
+    let name = span_name.split(['(', '`', ':']).next().unwrap_or(span_name);
+    name.split_whitespace()
+        .filter(|token| {
+            // ids and epochs, in hex or decimal
+            !(token.chars().all(|c| c.is_ascii_hexdigit())
+                && token.chars().any(|c| c.is_ascii_digit()))
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Splits an executor span into its type and operator id.
+///
+/// The executor id is printed in hex as `actor_id << 32 | operator_id`, so the lower 32 bits
+/// are shared by all actors of the same fragment.
+///
+/// # Example Input:
+/// - "HashJoin 1500000003" → ("HashJoin", 3)
+/// - "StreamScan 1EF68400002736" → ("StreamScan", 0x2736)
+pub(crate) fn parse_executor(span_name: &str) -> Option<(&str, u32)> {
+    let (kind, id) = span_name.rsplit_once(' ')?;
+    if kind == "Epoch" || kind.contains(' ') || !kind.starts_with(|c: char| c.is_ascii_uppercase())
+    {
+        return None;
+    }
+    let id = u64::from_str_radix(id, 16).ok()?;
+    Some((kind, id as u32))
+}
+
+/// Extracts and parses the trees of all actors in a dump file's content, keyed by actor id.
+pub fn parse_actor_trees(content: &str) -> anyhow::Result<BTreeMap<u32, TreeView>> {
+    let actor_traces = extract_actor_traces(content)
+        .map_err(|e| anyhow::anyhow!("Failed to extract actor traces from file: {}", e))?;
+    parse_traces(&actor_traces)
+}
+
+/// Parses the trace of each actor into a tree, keyed by actor id.
+pub(crate) fn parse_traces<'a, M>(actor_traces: M) -> anyhow::Result<BTreeMap<u32, TreeView>>
+where
+    M: IntoIterator<Item = (&'a u32, &'a String)>,
+{
+    actor_traces
+        .into_iter()
+        .map(|(actor_id, trace)| Ok((*actor_id, parse_tree_from_trace(trace)?)))
+        .collect()
+}
+
+/// Joins span names like `Actor 17 > Epoch 8318328637423616 > HashAgg 1100000005`, where
+/// the job name is omitted from the root span.
+pub(crate) fn format_span_path<S: AsRef<str>>(names: &[S]) -> String {
+    names
+        .iter()
+        .enumerate()
+        .map(|(depth, name)| {
+            let name = name.as_ref();
+            if depth == 0 {
+                name.split(':').next().unwrap_or(name)
+            } else {
+                name
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" > ")
+}
+
 pub(crate) fn parse_tree_from_trace(trace: &str) -> anyhow::Result<TreeView> {
     if trace.trim().starts_with("{") {
         // JSON usually starts with `{`
@@ -97,3 +189,24 @@ pub(crate) fn parse_tree_from_trace(trace: &str) -> anyhow::Result<TreeView> {
             .map_err(|e| anyhow::anyhow!("Failed to parse actor trace text: {}", e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::await_tree::utils::normalize_span_name;
+
+    #[test]
+    fn test_normalize_span_name() {
+        for (span_name, normalized) in [
+            ("Actor 17: `mv`", "Actor"),
+            ("Epoch 8318328637423616", "Epoch"),
+            ("HashAgg 1100000005", "HashAgg"),
+            ("StreamScan 1EF68400002736", "StreamScan"),
+            ("LocalInput (actor 122807)", "LocalInput"),
+            ("RemoteOutput (actor 5)", "RemoteOutput"),
+            ("store_get", "store_get"),
+            ("fetch_block 42", "fetch_block"),
+        ] {
+            assert_eq!(normalize_span_name(span_name), normalized, "{}", span_name);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::{Display, Formatter};
+
+use crate::await_tree::analyze::AnalyzeSummary;
+use crate::await_tree::finding::FindingKind;
+
+/// Overall outcome of an analysis, for alerting from cron jobs and CI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Healthy,
+    /// A bottleneck other than slow storage operations was found, e.g. a slow executor or a
+    /// wait-for cycle.
+    Bottleneck,
+    /// Only slow storage operations were found.
+    IoBound,
+    /// The dump could not be read or parsed, or contains no actor, e.g. because fetching it
+    /// failed.
+    ParseError,
+}
+
+impl Verdict {
+    /// Returns the verdict of the result of an analysis entry point such as
+    /// [`bottleneck_detect_from_file`](crate::await_tree::bottleneck_detect_from_file).
+    pub fn of(result: &anyhow::Result<AnalyzeSummary>) -> Self {
+        match result {
+            Ok(summary) => summary.verdict(),
+            Err(_) => Verdict::ParseError,
+        }
+    }
+
+    /// Returns the process exit code of the verdict. Codes 1 and 2 are left to other errors
+    /// and usage errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Verdict::Healthy => 0,
+            Verdict::Bottleneck => 3,
+            Verdict::IoBound => 4,
+            Verdict::ParseError => 5,
+        }
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Verdict::Healthy => "healthy",
+            Verdict::Bottleneck => "bottleneck",
+            Verdict::IoBound => "IO bound",
+            Verdict::ParseError => "parse error",
+        };
+        f.write_str(s)
+    }
+}
+
+impl AnalyzeSummary {
+    pub fn verdict(&self) -> Verdict {
+        if self.total_actors_analyzed == 0 {
+            return Verdict::ParseError;
+        }
+        let bottleneck = !self.wait_cycles.is_empty()
+            || self
+                .findings
+                .iter()
+                .any(|finding| finding.kind != FindingKind::IoBound);
+        if bottleneck {
+            Verdict::Bottleneck
+        } else if !self.io_bound_actors.is_empty() {
+            Verdict::IoBound
+        } else {
+            Verdict::Healthy
+        }
+    }
+
+    /// Returns a one-line summary with the verdict and the top finding, e.g. for a watchdog.
+    pub fn headline(&self) -> String {
+        let verdict = self.verdict();
+        let mut headline = format!(
+            "{}: {} findings, {} wait-for cycles in {} actors",
+            verdict,
+            self.findings.len(),
+            self.wait_cycles.len(),
+            self.total_actors_analyzed
+        );
+        if let Some(finding) = self.findings.first() {
+            headline += &format!(
+                "; top: {} at `{}` [{:.3}s] in actor {}",
+                finding.kind,
+                finding.span,
+                finding.elapsed_ns as f64 / 1_000_000_000.0,
+                finding.actor_id
+            );
+        }
+        headline
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use std::collections::BTreeMap;
+    use std::str::FromStr;
+
+    use crate::await_tree::{bottleneck_detect_from_file, AnalyzeSummary, TreeView, Verdict};
+
+    #[test]
+    fn test_verdict() -> Result<()> {
+        let io_bound = r#"Actor 3: `mv` [1000.000s]
+  Epoch 8251479171792896 [!!! 900.000s]
+    Materialize 300000007 [!!! 900.000s]
+      store_get [!!! 900.000s]
+"#;
+        let trees = BTreeMap::from([(3, TreeView::from_str(io_bound).unwrap())]);
+        let summary = AnalyzeSummary::from_trees(&trees);
+        assert_eq!(summary.verdict(), Verdict::IoBound);
+        assert_eq!(summary.verdict().exit_code(), 4);
+        assert_eq!(
+            summary.headline(),
+            "IO bound: 1 findings, 0 wait-for cycles in 1 actors; top: IO bound at `store_get` [900.000s] in actor 3"
+        );
+
+        let bottleneck = bottleneck_detect_from_file("samples/dump_agg_bottleneck.txt");
+        assert_eq!(Verdict::of(&bottleneck), Verdict::Bottleneck);
+        assert_eq!(Verdict::of(&bottleneck).exit_code(), 3);
+
+        // A root span without a quoted job name is analyzed as well.
+        let nameless = r#"Actor 1 [20.000s]
+  Epoch 8251479171792896 [!!! 15.000s]
+    Materialize 100000007 [!!! 15.000s]
+      store_get [!!! 15.000s]
+"#;
+        let trees = BTreeMap::from([(1, TreeView::from_str(nameless).unwrap())]);
+        let summary = AnalyzeSummary::from_trees(&trees);
+        assert_eq!(summary.verdict(), Verdict::IoBound);
+        assert!(summary.to_string().contains("unknown"));
+
+        let healthy = bottleneck_detect_from_file("samples/dump_healthy.txt");
+        assert_eq!(Verdict::of(&healthy), Verdict::Healthy);
+        assert_eq!(Verdict::of(&healthy).exit_code(), 0);
+
+        // A dump without actors, e.g. from a failed fetch, is not healthy.
+        assert_eq!(AnalyzeSummary::new().verdict(), Verdict::ParseError);
+        assert_eq!(
+            Verdict::of(&bottleneck_detect_from_file("no/such/dump.txt")),
+            Verdict::ParseError
+        );
+        assert_eq!(Verdict::ParseError.exit_code(), 5);
+        Ok(())
+    }
+}
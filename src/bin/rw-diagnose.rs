@@ -0,0 +1,351 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command-line interface of the await-tree analyzer.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rw_diagnose_tools::await_tree::{
+    parse_actor_trees, stdout_supports_color, AnalyzeConfig, AnalyzeSummary, BlameChain,
+    ChromeTrace, FoldedStacks, RenderOptions, SlowThreshold, StackWeight, TreeView, Verdict,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "rw-diagnose",
+    version,
+    about = "Toolset for diagnosing RisingWave clusters."
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Analyzes await-tree dumps and reports the bottlenecks.
+    ///
+    /// Exits with 0 if healthy, 3 if a bottleneck was found, 4 if only IO-bound actors were
+    /// found, and 5 if a dump could not be read or parsed or contains no actors.
+    Analyze {
+        #[command(flatten)]
+        input: InputArgs,
+        #[arg(long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+        /// Only prints a one-line summary with the verdict and the top finding.
+        #[arg(short, long)]
+        quiet: bool,
+        #[command(flatten)]
+        analyze: AnalyzeArgs,
+        #[command(flatten)]
+        render: RenderArgs,
+    },
+    /// Prints the await-trees of all actors in the text format.
+    Transcribe {
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        render: RenderArgs,
+    },
+    /// Shows the await-tree of one actor with its findings and blame chain.
+    Show {
+        /// Id of the actor to show.
+        #[arg(long)]
+        actor: u32,
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        analyze: AnalyzeArgs,
+        #[command(flatten)]
+        render: RenderArgs,
+    },
+    /// Prints elapsed time statistics of the actors and spans.
+    Stats {
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        analyze: AnalyzeArgs,
+    },
+}
+
+#[derive(Args)]
+struct InputArgs {
+    /// Await-tree dump files, e.g. one per compute node. Reads stdin if none is given or for
+    /// `-`.
+    files: Vec<PathBuf>,
+}
+
+#[derive(Args)]
+struct AnalyzeArgs {
+    /// Slow span threshold in seconds, or `adaptive` to derive it from the dump.
+    #[arg(long, default_value = "10", value_parser = parse_threshold)]
+    threshold: SlowThreshold,
+}
+
+#[derive(Args)]
+struct RenderArgs {
+    /// Only shows the paths leading to slow spans.
+    #[arg(long)]
+    prune: bool,
+    /// Hides the spans deeper than this.
+    #[arg(long)]
+    max_depth: Option<usize>,
+    /// Only shows this many of the slowest children of each span.
+    #[arg(long)]
+    max_children: Option<usize>,
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Markdown,
+    Html,
+    /// Graphviz DOT of the actor graph.
+    Dot,
+    /// Folded stacks for flamegraphs, weighted by elapsed time in microseconds.
+    Folded,
+    /// Chrome trace JSON for Perfetto.
+    ChromeTrace,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+fn parse_threshold(value: &str) -> Result<SlowThreshold, String> {
+    if value == "adaptive" {
+        return Ok(SlowThreshold::adaptive());
+    }
+    let secs: f64 = value
+        .parse()
+        .map_err(|e| format!("expected seconds or `adaptive`: {}", e))?;
+    Duration::try_from_secs_f64(secs)
+        .map(SlowThreshold::Fixed)
+        .map_err(|e| e.to_string())
+}
+
+impl AnalyzeArgs {
+    fn config(&self, render: Option<&RenderArgs>) -> AnalyzeConfig {
+        AnalyzeConfig {
+            slow_threshold: self.threshold,
+            render_options: render.map(RenderArgs::options).unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+impl RenderArgs {
+    fn options(&self) -> RenderOptions {
+        RenderOptions {
+            prune_fast: self.prune,
+            max_depth: self.max_depth,
+            max_children: self.max_children,
+        }
+    }
+
+    fn color(&self) -> bool {
+        match self.color {
+            ColorChoice::Auto => stdout_supports_color(),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// Failure to read or parse an input dump, reported with [`Verdict::ParseError`].
+#[derive(Debug)]
+struct InputError(anyhow::Error);
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl InputArgs {
+    /// Reads and parses the trees of all input dumps, keyed by actor id.
+    fn read_trees(&self) -> Result<BTreeMap<u32, TreeView>, InputError> {
+        let stdin = [PathBuf::from("-")];
+        let files = if self.files.is_empty() {
+            &stdin[..]
+        } else {
+            &self.files[..]
+        };
+        let mut trees = BTreeMap::new();
+        for file in files {
+            let content = if file.as_os_str() == "-" {
+                let mut content = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut content)
+                    .map_err(|e| InputError(anyhow::anyhow!("Failed to read stdin: {}", e)))?;
+                content
+            } else {
+                std::fs::read_to_string(file).map_err(|e| {
+                    InputError(anyhow::anyhow!("Failed to read {}: {}", file.display(), e))
+                })?
+            };
+            let dump_trees = parse_actor_trees(&content).map_err(|e| {
+                InputError(anyhow::anyhow!("Failed to parse {}: {}", file.display(), e))
+            })?;
+            // Don't report an empty, truncated or unrelated file as a healthy cluster.
+            if dump_trees.is_empty() {
+                return Err(InputError(anyhow::anyhow!(
+                    "No actor traces found in {}",
+                    file.display()
+                )));
+            }
+            for (actor_id, tree) in dump_trees {
+                if trees.insert(actor_id, tree).is_some() {
+                    eprintln!(
+                        "Warning: actor {} appears in several dumps, keeping the one in {}",
+                        actor_id,
+                        file.display()
+                    );
+                }
+            }
+        }
+        Ok(trees)
+    }
+}
+
+/// Runs the command and returns the verdict of the analysis, which is healthy for the commands
+/// that only print the dump.
+fn run(command: Command, out: &mut impl Write) -> anyhow::Result<Verdict> {
+    match command {
+        Command::Analyze {
+            input,
+            format,
+            quiet,
+            analyze,
+            render,
+        } => {
+            let trees = input.read_trees()?;
+            let summary =
+                AnalyzeSummary::from_trees_with_config(&trees, &analyze.config(Some(&render)));
+            if quiet {
+                writeln!(out, "{}", summary.headline())?;
+                return Ok(summary.verdict());
+            }
+            match format {
+                Format::Text => write!(out, "{}", summary)?,
+                Format::Markdown => write!(out, "{}", summary.markdown())?,
+                Format::Html => write!(out, "{}", summary.html(&trees))?,
+                Format::Dot => write!(out, "{}", summary.dot(&trees))?,
+                Format::Folded => {
+                    let mut stacks = FoldedStacks::new(StackWeight::Elapsed);
+                    stacks.add_trees(&trees);
+                    write!(out, "{}", stacks)?;
+                }
+                Format::ChromeTrace => writeln!(out, "{}", ChromeTrace::new(&trees).to_json()?)?,
+            }
+            return Ok(summary.verdict());
+        }
+        Command::Transcribe { input, render } => {
+            let (options, color) = (render.options(), render.color());
+            for (actor_id, tree) in input.read_trees()? {
+                writeln!(out, ">> Actor {}", actor_id)?;
+                writeln!(out, "{}", tree.render().options(options).color(color))?;
+            }
+        }
+        Command::Show {
+            actor,
+            input,
+            analyze,
+            render,
+        } => {
+            let trees = input.read_trees()?;
+            let tree = trees
+                .get(&actor)
+                .ok_or_else(|| anyhow::anyhow!("Actor {} not found in the dump", actor))?;
+            let summary =
+                AnalyzeSummary::from_trees_with_config(&trees, &analyze.config(Some(&render)));
+            let findings = summary
+                .findings()
+                .iter()
+                .filter(|finding| finding.actor_id == actor)
+                .collect::<Vec<_>>();
+            let mut tree_render = tree
+                .render()
+                .options(render.options())
+                .slow_threshold(summary.slow_threshold_ns())
+                .color(render.color());
+            for finding in &findings {
+                tree_render = tree_render.bottleneck(&finding.evidence.path_indices);
+            }
+            writeln!(out, "{}", tree_render)?;
+            for finding in &findings {
+                writeln!(out, "{}", finding)?;
+                for hint in &finding.hints {
+                    writeln!(out, "  Hint: {}", hint)?;
+                    writeln!(out, "    Remediation: {}", hint.remediation)?;
+                }
+            }
+            if !findings.is_empty() {
+                writeln!(out)?;
+            }
+            write!(
+                out,
+                "{}",
+                BlameChain::with_slow_threshold(&trees, actor, summary.slow_threshold_ns())?
+            )?;
+        }
+        Command::Stats { input, analyze } => {
+            let trees = input.read_trees()?;
+            let summary = AnalyzeSummary::from_trees_with_config(&trees, &analyze.config(None));
+            writeln!(out, "Total actors analyzed: {}", trees.len())?;
+            write!(out, "{}", summary.stats())?;
+        }
+    }
+    Ok(Verdict::Healthy)
+}
+
+fn main() {
+    let command = Cli::parse().command;
+    let mut out = std::io::stdout().lock();
+    let result = run(command, &mut out).and_then(|verdict| {
+        out.flush()?;
+        Ok(verdict)
+    });
+    let exit_code = match result {
+        Ok(verdict) => verdict.exit_code(),
+        // Output piped into e.g. `head` is not an error.
+        Err(e)
+            if e.downcast_ref::<std::io::Error>()
+                .is_some_and(|e| e.kind() == std::io::ErrorKind::BrokenPipe) =>
+        {
+            0
+        }
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            if e.is::<InputError>() {
+                Verdict::ParseError.exit_code()
+            } else {
+                1
+            }
+        }
+    };
+    std::process::exit(exit_code);
+}
@@ -0,0 +1,78 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Golden tests comparing the analyze summary of each dump `samples/<name>.txt` against the
+//! checked-in report `samples/<name>.expected`.
+//!
+//! Run with `UPDATE_EXPECTED=1` to regenerate the expected reports after an intended change.
+
+use std::path::Path;
+
+use rw_diagnose_tools::await_tree::bottleneck_detect_from_file;
+
+#[test]
+fn test_golden_reports() -> anyhow::Result<()> {
+    let update = std::env::var_os("UPDATE_EXPECTED").is_some();
+    let samples = Path::new(env!("CARGO_MANIFEST_DIR")).join("samples");
+    let mut dumps = std::fs::read_dir(&samples)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    dumps.retain(|path| path.extension().is_some_and(|ext| ext == "txt"));
+    dumps.sort();
+    assert!(
+        !dumps.is_empty(),
+        "no sample dumps in {}",
+        samples.display()
+    );
+
+    let mut mismatches = vec![];
+    for dump in dumps {
+        let report = bottleneck_detect_from_file(dump.to_str().unwrap())?.to_string();
+        // The report must not depend on the iteration order of any hash map.
+        let again = bottleneck_detect_from_file(dump.to_str().unwrap())?.to_string();
+        assert_eq!(
+            report,
+            again,
+            "non-deterministic report of {}",
+            dump.display()
+        );
+
+        let expected_path = dump.with_extension("expected");
+        if update {
+            std::fs::write(&expected_path, &report)?;
+            continue;
+        }
+        let expected = std::fs::read_to_string(&expected_path).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to read {}, run with UPDATE_EXPECTED=1 to create it: {}",
+                expected_path.display(),
+                e
+            )
+        })?;
+        if report != expected {
+            mismatches.push(format!(
+                "{}:\n--- expected ---\n{}\n--- actual ---\n{}",
+                expected_path.display(),
+                expected,
+                report
+            ));
+        }
+    }
+    assert!(
+        mismatches.is_empty(),
+        "reports differ from the expected ones, run with UPDATE_EXPECTED=1 if intended:\n{}",
+        mismatches.join("\n")
+    );
+    Ok(())
+}